@@ -1,5 +1,8 @@
 use std::time::{Duration, Instant};
-use crate::storage::{ReadingSession, save_session, save_current_page, BookProgress};
+use chrono::{DateTime, Local};
+use uuid::Uuid;
+use crate::checkpoint::ActiveSession;
+use crate::storage::{chapter_index_at, chapters_covered, next_in_series, Chapter, Series, ReadingSession, BookId, BookMetadata, BookProgress, Library, LibraryStore, Note, NoteKind, ProgressUnit, ReadingRange, ReadingStats, ReadingStatus, TimeInterval};
 
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum TimerState {
@@ -8,35 +11,168 @@ pub enum TimerState {
     Paused,
 }
 
+/// A note being typed on the reading screen
+#[derive(Debug)]
+pub struct NoteDraft {
+    pub kind: NoteKind,
+    pub text: String,
+}
+
+impl NoteDraft {
+    pub fn toggle_kind(&mut self) {
+        self.kind = match self.kind {
+            NoteKind::Note => NoteKind::Quote,
+            NoteKind::Quote => NoteKind::Note,
+        };
+    }
+}
+
+/// Which part of the rating prompt has focus
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RatingStage {
+    Stars,
+    Review,
+}
+
+/// The rating and review asked for when a book reaches 100%
+#[derive(Debug)]
+pub struct RatingPrompt {
+    pub stage: RatingStage,
+    pub rating: f32,
+    pub review: String,
+}
+
+impl RatingPrompt {
+    pub fn adjust(&mut self, delta: f32) {
+        self.rating = (self.rating + delta).clamp(0.5, 5.0);
+    }
+}
+
+/// What to read after finishing a book in a series
+#[derive(Debug)]
+pub enum NextUp {
+    /// The next want-to-read book of the series in the library
+    Open {
+        book_id: BookId,
+        book_title: String,
+        series: Series,
+    },
+    /// The following number is not in the library yet
+    Add { series: Series, authors: Vec<String> },
+}
+
+/// Where focus mode is in its cycle of blocks and breaks
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FocusPhase {
+    /// Counting down running time from when the timer showed `started_at`
+    Block { started_at: Duration },
+    /// The timer is paused until `ends`
+    Break { ends: Instant },
+}
+
 #[derive(Debug)]
 pub struct AppState {
     pub timer_state: TimerState,
     pub start_time: Option<Instant>,
     pub elapsed_before_pause: Duration,
+    /// Finished stretches of running time in the open session
+    pub intervals: Vec<TimeInterval>,
+    /// Wall-clock start of the stretch the timer is running now
+    pub running_since: Option<DateTime<Local>>,
+    /// Position and length in `unit`
     pub current_page: usize,
     pub total_pages: usize,
+    pub unit: ProgressUnit,
+    /// The part of the book progress is measured over
+    pub range: ReadingRange,
+    pub chapters: Vec<Chapter>,
     pub book_title: String,
+    pub metadata: BookMetadata,
+    pub status: ReadingStatus,
+    pub started_at: Option<DateTime<Local>>,
+    pub finished_at: Option<DateTime<Local>>,
     pub session_start_page: usize,
-    pub total_time_secs: u64,
-    pub total_sessions: usize,
-    pub book_index: usize,
+    /// Id the running session will be saved under, so notes can point at it
+    pub session_id: Uuid,
+    pub note_draft: Option<NoteDraft>,
+    pub note_count: usize,
+    pub rating: Option<f32>,
+    pub review: Option<String>,
+    pub rating_prompt: Option<RatingPrompt>,
+    /// The prompt has already been shown since this book was opened
+    rating_prompted: bool,
+    /// Offered once a series book is finished
+    pub next_up: Option<NextUp>,
+    /// Last key press on the reading screen
+    pub last_activity: Instant,
+    /// The book's idle threshold override, see `BookProgress::idle_minutes`
+    pub idle_minutes: Option<u32>,
+    /// Idle threshold from the configuration
+    pub default_idle_minutes: Option<u32>,
+    /// Set to the last activity while asking whether reading went on while idle
+    pub idle_prompt: Option<Instant>,
+    /// Focus mode's phase, while it is on
+    pub focus: Option<FocusPhase>,
+    /// Focus blocks completed in the open session
+    pub focus_blocks: u32,
+    /// Lengths of a focus block and of the break after it, from the configuration
+    pub focus_minutes: u32,
+    pub break_minutes: u32,
+    /// Time and sessions for the current read
+    pub run_stats: ReadingStats,
+    /// Time and sessions across every read of the book
+    pub lifetime_stats: ReadingStats,
+    pub read_number: usize,
+    pub book_id: BookId,
+    /// Another instance owns the library, so nothing here is saved
+    pub read_only: bool,
+    /// Why the open session could not be checkpointed, until a write succeeds
+    pub checkpoint_error: Option<String>,
 }
 
 impl AppState {
-    pub fn new(book_index: usize, progress: &BookProgress) -> Self {
-        let (total_time_secs, total_sessions) = crate::storage::get_statistics(progress);
+    pub fn new(progress: &BookProgress) -> Self {
+        let (run_stats, lifetime_stats) = crate::storage::get_statistics(progress);
 
         Self {
             timer_state: TimerState::Stopped,
             start_time: None,
             elapsed_before_pause: Duration::ZERO,
+            intervals: Vec::new(),
+            running_since: None,
             current_page: progress.current_page,
             total_pages: progress.total_pages,
+            unit: progress.unit,
+            range: progress.reading_range(),
+            chapters: progress.chapters.clone(),
             book_title: progress.book_title.clone(),
+            metadata: progress.metadata.clone(),
+            status: progress.status.clone(),
+            started_at: progress.started_at,
+            finished_at: progress.finished_at,
             session_start_page: progress.current_page,
-            total_time_secs,
-            total_sessions,
-            book_index,
+            session_id: Uuid::new_v4(),
+            note_draft: None,
+            note_count: progress.notes.len(),
+            rating: progress.rating,
+            review: progress.review.clone(),
+            rating_prompt: None,
+            rating_prompted: false,
+            next_up: None,
+            last_activity: Instant::now(),
+            idle_minutes: progress.idle_minutes,
+            default_idle_minutes: None,
+            idle_prompt: None,
+            focus: None,
+            focus_blocks: 0,
+            focus_minutes: 25,
+            break_minutes: 5,
+            run_stats,
+            lifetime_stats,
+            read_number: progress.read_number(),
+            book_id: progress.id,
+            read_only: false,
+            checkpoint_error: None,
         }
     }
 
@@ -50,10 +186,15 @@ impl AppState {
     }
 
     pub fn start(&mut self) {
+        if self.timer_state == TimerState::Stopped {
+            self.session_id = Uuid::new_v4();
+            self.intervals.clear();
+            self.session_start_page = self.current_page;
+        }
         if self.timer_state != TimerState::Running {
             self.timer_state = TimerState::Running;
             self.start_time = Some(Instant::now());
-            self.session_start_page = self.current_page;
+            self.running_since = Some(Local::now());
         }
     }
 
@@ -61,94 +202,440 @@ impl AppState {
         if self.timer_state == TimerState::Running {
             self.elapsed_before_pause += self.start_time.unwrap().elapsed();
             self.start_time = None;
+            self.close_interval(Local::now());
+            self.timer_state = TimerState::Paused;
+        }
+    }
+
+    /// End the running stretch at `end`
+    fn close_interval(&mut self, end: DateTime<Local>) {
+        if let Some(start) = self.running_since.take() {
+            self.intervals.push(TimeInterval { start, end: end.max(start) });
+        }
+    }
+
+    /// Every stretch the open session has run so far, including the current one
+    fn intervals_so_far(&self) -> Vec<TimeInterval> {
+        let mut intervals = self.intervals.clone();
+        if let Some(start) = self.running_since {
+            intervals.push(TimeInterval { start, end: Local::now() });
+        }
+        intervals
+    }
+
+    /// Wall-clock time of the last key press, while the idle prompt is showing
+    fn idle_since(&self) -> Option<DateTime<Local>> {
+        let since = self.idle_prompt?;
+        Some(Local::now() - chrono::Duration::from_std(since.elapsed()).unwrap_or_default())
+    }
+
+    /// The open session for checkpointing, if the timer is running or paused
+    pub fn active_session(&self) -> Option<ActiveSession> {
+        if self.timer_state == TimerState::Stopped {
+            return None;
+        }
+        Some(ActiveSession {
+            book_id: self.book_id,
+            session_id: self.session_id,
+            session_start_page: self.session_start_page,
+            current_page: self.current_page,
+            elapsed_secs: self.elapsed().as_secs(),
+            running: self.timer_state == TimerState::Running,
+            focus_blocks: self.focus_blocks,
+            intervals: self.intervals_so_far(),
+            checkpointed_at: Local::now(),
+        })
+    }
+
+    /// Pick a checkpointed session back up, continuing the timer from where it was saved
+    ///
+    /// A session checkpointed while paused comes back paused.
+    pub fn resume_session(&mut self, active: &ActiveSession) {
+        self.session_id = active.session_id;
+        self.session_start_page = active.session_start_page;
+        self.current_page = active.current_page.min(self.total_pages);
+        self.elapsed_before_pause = Duration::from_secs(active.elapsed_secs);
+        self.intervals = active.intervals.clone();
+        self.focus_blocks = active.focus_blocks;
+        if active.running {
+            self.timer_state = TimerState::Running;
+            self.start_time = Some(Instant::now());
+            self.running_since = Some(Local::now());
+        } else {
+            self.timer_state = TimerState::Paused;
+            self.start_time = None;
+            self.running_since = None;
+        }
+        self.touch();
+    }
+
+    /// Note a key press, for idle detection
+    pub fn touch(&mut self) {
+        self.last_activity = Instant::now();
+    }
+
+    /// How long without a key press counts as idle, if idle detection is on
+    fn idle_threshold(&self) -> Option<Duration> {
+        let minutes = match self.idle_minutes {
+            Some(minutes) => minutes,
+            None => self.default_idle_minutes?,
+        };
+        (minutes > 0).then(|| Duration::from_secs(u64::from(minutes) * 60))
+    }
+
+    /// Ask whether reading went on once the running timer has seen no key for too long
+    pub fn check_idle(&mut self) {
+        if self.timer_state == TimerState::Running
+            && self.idle_prompt.is_none()
+            && let Some(threshold) = self.idle_threshold()
+            && self.last_activity.elapsed() >= threshold
+        {
+            self.idle_prompt = Some(self.last_activity);
+        }
+    }
+
+    /// Time the timer ran since the last key press, while the idle prompt is showing
+    ///
+    /// Only the running stretches count, so a focus break in between is left out.
+    pub fn idle_time(&self) -> Duration {
+        let Some(since) = self.idle_since() else {
+            return Duration::ZERO;
+        };
+        self.intervals_so_far()
+            .iter()
+            .filter_map(|i| (i.end - i.start.max(since)).to_std().ok())
+            .sum()
+    }
+
+    /// Take the running time since the last key press out of the session
+    ///
+    /// The clock is left without a running stretch; the caller restarts or pauses it.
+    fn cut_idle(&mut self) {
+        let Some(since) = self.idle_since() else {
+            return;
+        };
+        self.elapsed_before_pause = self.elapsed().saturating_sub(self.idle_time());
+        self.start_time = None;
+        self.close_interval(Local::now());
+        self.intervals.retain(|i| i.start < since);
+        if let Some(last) = self.intervals.last_mut() {
+            last.end = last.end.min(since);
+        }
+    }
+
+    /// Drop the idle stretch from the session and keep the timer running
+    pub fn trim_idle(&mut self) {
+        self.cut_idle();
+        if self.timer_state == TimerState::Running {
+            self.start_time = Some(Instant::now());
+            self.running_since = Some(Local::now());
+        }
+        self.idle_prompt = None;
+    }
+
+    /// Pause the timer as if it had been paused at the last key press
+    pub fn pause_at_last_activity(&mut self) {
+        self.cut_idle();
+        if self.timer_state == TimerState::Running {
             self.timer_state = TimerState::Paused;
         }
+        self.idle_prompt = None;
     }
 
-    pub fn stop(&mut self) {
+    /// Turn focus mode on, starting a block at the current timer, or off
+    pub fn toggle_focus(&mut self) {
+        self.focus = match self.focus {
+            Some(_) => None,
+            None => Some(FocusPhase::Block { started_at: self.elapsed() }),
+        };
+    }
+
+    fn focus_block(&self) -> Duration {
+        Duration::from_secs(u64::from(self.focus_minutes.max(1)) * 60)
+    }
+
+    /// Time left in the current focus block or break
+    pub fn focus_remaining(&self) -> Option<Duration> {
+        match self.focus? {
+            FocusPhase::Block { started_at } => {
+                Some(self.focus_block().saturating_sub(self.elapsed().saturating_sub(started_at)))
+            }
+            FocusPhase::Break { ends } => Some(ends.saturating_duration_since(Instant::now())),
+        }
+    }
+
+    /// Move focus mode on when a block or break runs out
+    ///
+    /// A finished block pauses the timer for a break, and the end of the break
+    /// starts it again; both return true so the caller can ring the bell.
+    /// Starting the timer during a break skips the rest of it.
+    pub fn tick_focus(&mut self) -> bool {
+        match self.focus {
+            Some(FocusPhase::Block { .. }) => {
+                if self.timer_state != TimerState::Running || self.focus_remaining() != Some(Duration::ZERO) {
+                    return false;
+                }
+                self.pause();
+                self.focus_blocks += 1;
+                let length = Duration::from_secs(u64::from(self.break_minutes) * 60);
+                self.focus = Some(FocusPhase::Break { ends: Instant::now() + length });
+                true
+            }
+            Some(FocusPhase::Break { ends }) => {
+                if self.timer_state == TimerState::Running {
+                    self.focus = Some(FocusPhase::Block { started_at: self.elapsed() });
+                    return false;
+                }
+                if Instant::now() < ends {
+                    return false;
+                }
+                if self.timer_state == TimerState::Paused {
+                    self.start();
+                }
+                self.focus = Some(FocusPhase::Block { started_at: self.elapsed() });
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn stop(&mut self, store: &mut LibraryStore) {
         if self.timer_state == TimerState::Running {
             self.pause();
         }
 
         // Save session if there was any time spent
         if !self.elapsed_before_pause.is_zero() {
+            let now = Local::now();
+            let intervals = std::mem::take(&mut self.intervals);
             let session = ReadingSession {
+                id: self.session_id,
                 book_title: self.book_title.clone(),
                 start_page: self.session_start_page,
                 end_page: self.current_page,
+                unit: self.unit,
+                chapters: chapters_covered(&self.chapters, self.session_start_page, self.current_page),
                 duration_secs: self.elapsed_before_pause.as_secs(),
-                timestamp: chrono::Local::now(),
+                started_at: intervals.first().map_or(now, |i| i.start),
+                timestamp: now,
+                intervals,
+                focus_blocks: self.focus_blocks,
             };
 
-            if let Err(e) = save_session(self.book_index, &session) {
+            if let Err(e) = store.save_session(self.book_id, &session) {
                 eprintln!("Error saving session: {}", e);
             } else {
                 // Update total time and sessions count
-                self.total_time_secs += self.elapsed_before_pause.as_secs();
-                self.total_sessions += 1;
+                self.run_stats.add_session(self.elapsed_before_pause.as_secs());
+                self.lifetime_stats.add_session(self.elapsed_before_pause.as_secs());
+
+                // The session may have started or finished the book
+                let was_finished = self.status == ReadingStatus::Finished;
+                if let Some(book) = store.library().book(self.book_id) {
+                    self.refresh_status(book);
+                }
+                if !was_finished && self.status == ReadingStatus::Finished {
+                    self.offer_next_in_series(store.library());
+                }
             }
         } else {
             // If no time was spent, just save the current page
-            if let Err(e) = save_current_page(self.book_index, self.current_page) {
-                eprintln!("Error saving current page: {}", e);
-            }
+            store.save_current_page(self.book_id, self.current_page);
         }
 
         self.timer_state = TimerState::Stopped;
         self.start_time = None;
         self.elapsed_before_pause = Duration::ZERO;
+        self.intervals.clear();
+        self.running_since = None;
         self.session_start_page = self.current_page;
+        self.reset_focus();
     }
 
-    pub fn save_page(&self) {
-        if let Err(e) = save_current_page(self.book_index, self.current_page) {
-            eprintln!("Error saving current page: {}", e);
-        }
+    /// Suggest the next book of the series, or adding it when the library lacks it
+    fn offer_next_in_series(&mut self, library: &Library) {
+        let Some(series) = &self.metadata.series else {
+            return;
+        };
+        self.next_up = match next_in_series(library, series) {
+            Some(book) => Some(NextUp::Open {
+                book_id: book.id,
+                book_title: book.book_title.clone(),
+                series: book.metadata.series.clone().unwrap_or_else(|| series.clone()),
+            }),
+            None if series.total.is_some_and(|total| series.number >= total) => None,
+            // Already read, reading or abandoned
+            None if library.books.iter().any(|book| {
+                book.metadata
+                    .series
+                    .as_ref()
+                    .is_some_and(|other| other.same_series(series) && other.number == series.number + 1)
+            }) => None,
+            None => Some(NextUp::Add {
+                series: series.clone(),
+                authors: self.metadata.authors.clone(),
+            }),
+        };
     }
 
-    pub fn load_book(&mut self, book_index: usize, progress: &BookProgress) {
-        let (total_time_secs, total_sessions) = crate::storage::get_statistics(progress);
+    fn refresh_status(&mut self, progress: &BookProgress) {
+        self.status = progress.status.clone();
+        self.started_at = progress.started_at;
+        self.finished_at = progress.finished_at;
+    }
 
-        self.book_index = book_index;
+    pub fn save_page(&self, store: &mut LibraryStore) {
+        store.save_current_page(self.book_id, self.current_page);
+    }
+
+    pub fn load_book(&mut self, progress: &BookProgress) {
+        let (run_stats, lifetime_stats) = crate::storage::get_statistics(progress);
+
+        self.book_id = progress.id;
         self.book_title = progress.book_title.clone();
+        self.metadata = progress.metadata.clone();
+        self.refresh_status(progress);
         self.total_pages = progress.total_pages;
+        self.unit = progress.unit;
+        self.range = progress.reading_range();
+        self.chapters = progress.chapters.clone();
+        self.note_count = progress.notes.len();
+        self.note_draft = None;
+        self.rating = progress.rating;
+        self.review = progress.review.clone();
+        self.rating_prompt = None;
+        self.rating_prompted = false;
+        self.next_up = None;
+        self.idle_minutes = progress.idle_minutes;
+        self.idle_prompt = None;
         self.current_page = progress.current_page;
         self.session_start_page = progress.current_page;
-        self.total_time_secs = total_time_secs;
-        self.total_sessions = total_sessions;
+        self.run_stats = run_stats;
+        self.lifetime_stats = lifetime_stats;
+        self.read_number = progress.read_number();
 
         // Reset timer state when switching books
         self.timer_state = TimerState::Stopped;
         self.start_time = None;
         self.elapsed_before_pause = Duration::ZERO;
+        self.intervals.clear();
+        self.running_since = None;
+        self.reset_focus();
     }
 
-    pub fn increment_page(&mut self) {
-        if self.current_page < self.total_pages {
-            self.current_page += 1;
+    /// Start focus mode over with a fresh block, as a new session begins
+    fn reset_focus(&mut self) {
+        self.focus_blocks = 0;
+        if self.focus.is_some() {
+            self.focus = Some(FocusPhase::Block { started_at: Duration::ZERO });
         }
     }
 
+    /// Move forward one step of the book's unit (a page, 10 locations, 30 seconds...)
+    pub fn increment_page(&mut self) {
+        self.add_pages(self.unit.small_step());
+    }
+
     pub fn decrement_page(&mut self) {
-        if self.current_page > 0 {
-            self.current_page = self.current_page.saturating_sub(1);
-        }
+        self.add_pages(-self.unit.small_step());
     }
 
-    pub fn add_pages(&mut self, delta: i32) {
-        let new_page = (self.current_page as i32 + delta).clamp(0, self.total_pages as i32);
+    pub fn add_pages(&mut self, delta: i64) {
+        let new_page = (self.current_page as i64 + delta).clamp(0, self.total_pages as i64);
         self.current_page = new_page as usize;
+        self.prompt_rating_if_done();
     }
 
-    pub fn progress(&self) -> f64 {
-        if self.total_pages == 0 {
-            0.0
+    /// Ask for a rating the first time the book reaches 100% after being opened
+    fn prompt_rating_if_done(&mut self) {
+        if self.progress() >= 1.0 && !self.rating_prompted {
+            self.rating_prompted = true;
+            self.rating_prompt = Some(RatingPrompt {
+                stage: RatingStage::Stars,
+                rating: self.rating.unwrap_or(3.0),
+                review: self.review.clone().unwrap_or_default(),
+            });
+        }
+    }
+
+    pub fn save_rating(&mut self, store: &mut LibraryStore) {
+        let Some(prompt) = self.rating_prompt.take() else {
+            return;
+        };
+        let review = Some(prompt.review.trim().to_string()).filter(|r| !r.is_empty());
+
+        if let Err(e) = store.set_review(self.book_id, prompt.rating, review.clone()) {
+            eprintln!("Error saving rating: {}", e);
         } else {
-            (self.current_page as f64 / self.total_pages as f64).min(1.0)
+            self.rating = Some(prompt.rating);
+            self.review = review;
         }
     }
 
-    pub fn pages_read_this_session(&self) -> i32 {
-        self.current_page as i32 - self.session_start_page as i32
+    pub fn start_note(&mut self) {
+        self.note_draft = Some(NoteDraft {
+            kind: NoteKind::Note,
+            text: String::new(),
+        });
+    }
+
+    /// Save the draft at the current page, tagged with the running session if any
+    pub fn save_note(&mut self, store: &mut LibraryStore) {
+        let Some(draft) = self.note_draft.take() else {
+            return;
+        };
+        if draft.text.trim().is_empty() {
+            return;
+        }
+
+        let note = Note {
+            id: Uuid::new_v4(),
+            kind: draft.kind,
+            text: draft.text.trim().to_string(),
+            page: self.current_page,
+            created_at: Local::now(),
+            session_id: (self.timer_state != TimerState::Stopped).then_some(self.session_id),
+        };
+        if let Err(e) = store.add_note(self.book_id, note) {
+            eprintln!("Error saving note: {}", e);
+        } else {
+            self.note_count += 1;
+        }
+    }
+
+    pub fn current_chapter(&self) -> Option<&Chapter> {
+        chapter_index_at(&self.chapters, self.current_page).map(|i| &self.chapters[i])
+    }
+
+    /// Distance to the next chapter, or to the end of the reading range in the last one
+    pub fn left_in_chapter(&self) -> Option<usize> {
+        let index = chapter_index_at(&self.chapters, self.current_page)?;
+        let end = self
+            .chapters
+            .get(index + 1)
+            .map_or(self.range.last, |next| next.start_page);
+        Some(end.saturating_sub(self.current_page))
+    }
+
+    /// Move to the start of the next chapter, if there is one
+    pub fn jump_to_next_chapter(&mut self) {
+        if let Some(next) = self.chapters.iter().find(|c| c.start_page > self.current_page) {
+            self.current_page = next.start_page.min(self.total_pages);
+            self.prompt_rating_if_done();
+        }
+    }
+
+    /// Fraction of the reading range covered
+    pub fn progress(&self) -> f64 {
+        self.range.progress(self.current_page)
+    }
+
+    /// Pages of the reading range covered since the session started; negative if moved back
+    pub fn pages_read_this_session(&self) -> i64 {
+        if self.current_page >= self.session_start_page {
+            self.range.read_between(self.session_start_page, self.current_page) as i64
+        } else {
+            -(self.range.read_between(self.current_page, self.session_start_page) as i64)
+        }
     }
 }
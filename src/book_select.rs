@@ -1,24 +1,506 @@
 use ratatui::{
-    layout::{Alignment, Constraint, Direction, Layout},
+    layout::{Alignment, Constraint, Direction, Layout, Rect},
     style::{Color, Modifier, Style},
     text::{Line, Span},
-    widgets::{Block, Borders, List, ListItem, Paragraph},
+    widgets::{Block, Borders, Clear, List, ListItem, Paragraph, Wrap},
     Frame,
 };
 
-use crate::storage::Library;
+use std::borrow::Cow;
+
+use crate::form::{FieldForm, FormState};
+use crate::isbn::normalize_isbn;
+use crate::storage::{
+    BookDetails, BookId, BookMetadata, BookProgress, Library, ProgressUnit, ReadingStatus, Series,
+};
 
 pub struct BookSelector {
+    /// Index into `visible_books`, not `Library::books`
     pub selected_index: usize,
     pub input_mode: InputMode,
-    pub new_book_title: String,
-    pub new_book_pages: String,
+    pub form: BookForm,
+    pub filter: StatusFilter,
+    pub sort: SortOrder,
+    /// Why the book is being abandoned, while typing it
+    pub abandon_reason: String,
+    /// Another instance owns the library, so nothing here is saved
+    pub read_only: bool,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum InputMode {
     Selection,
-    EnteringTitle,
-    EnteringPages,
+    AddingBook,
+    EditingBook(BookId),
+    ConfirmDelete(BookId),
+    AbandonReason(BookId),
+}
+
+/// How the library list is ordered
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SortOrder {
+    /// Grouped by reading status
+    Status,
+    /// Highest rated first, unrated books last
+    Rating,
+    /// Each series together in reading order, standalone books after
+    Series,
+}
+
+impl SortOrder {
+    fn next(self) -> Self {
+        match self {
+            SortOrder::Status => SortOrder::Rating,
+            SortOrder::Rating => SortOrder::Series,
+            SortOrder::Series => SortOrder::Status,
+        }
+    }
+
+    /// Heading shown above the run of books it returns the same value for
+    fn group(self, book: &BookProgress) -> Option<&str> {
+        match self {
+            SortOrder::Status => Some(book.status.label()),
+            SortOrder::Rating => None,
+            SortOrder::Series => Some(book.metadata.series.as_ref().map_or("Standalone", |s| s.name.as_str())),
+        }
+    }
+}
+
+/// Which books the library list shows
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum StatusFilter {
+    All,
+    Reading,
+    WantToRead,
+    Finished,
+    Abandoned,
+}
+
+impl StatusFilter {
+    pub fn next(self) -> Self {
+        match self {
+            StatusFilter::All => StatusFilter::Reading,
+            StatusFilter::Reading => StatusFilter::WantToRead,
+            StatusFilter::WantToRead => StatusFilter::Finished,
+            StatusFilter::Finished => StatusFilter::Abandoned,
+            StatusFilter::Abandoned => StatusFilter::All,
+        }
+    }
+
+    fn matches(self, status: &ReadingStatus) -> bool {
+        match self {
+            StatusFilter::All => true,
+            StatusFilter::Reading => *status == ReadingStatus::Reading,
+            StatusFilter::WantToRead => *status == ReadingStatus::WantToRead,
+            StatusFilter::Finished => *status == ReadingStatus::Finished,
+            StatusFilter::Abandoned => matches!(status, ReadingStatus::Abandoned { .. }),
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            StatusFilter::All => "All",
+            StatusFilter::Reading => "Reading",
+            StatusFilter::WantToRead => "Want to Read",
+            StatusFilter::Finished => "Finished",
+            StatusFilter::Abandoned => "Abandoned",
+        }
+    }
+}
+
+/// Fields of the add/edit form, in tab order
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FormField {
+    Title,
+    Unit,
+    Total,
+    Range,
+    Authors,
+    Series,
+    SeriesNumber,
+    Isbn,
+    Publisher,
+    Year,
+    Language,
+    Genres,
+    IdleMinutes,
+}
+
+impl FormField {
+    const ALL: [FormField; 13] = [
+        FormField::Title,
+        FormField::Unit,
+        FormField::Total,
+        FormField::Range,
+        FormField::Authors,
+        FormField::Series,
+        FormField::SeriesNumber,
+        FormField::Isbn,
+        FormField::Publisher,
+        FormField::Year,
+        FormField::Language,
+        FormField::Genres,
+        FormField::IdleMinutes,
+    ];
+
+    fn label(self, unit: ProgressUnit) -> &'static str {
+        match self {
+            FormField::Title => "Title",
+            FormField::Unit => "Progress In",
+            FormField::Total => match unit {
+                ProgressUnit::Pages => "Total Pages",
+                ProgressUnit::Percent => "Total",
+                ProgressUnit::Locations => "Total Locations",
+                ProgressUnit::Audio => "Total Runtime",
+            },
+            FormField::Range => "Reading Range",
+            FormField::Authors => "Authors",
+            FormField::Series => "Series",
+            FormField::SeriesNumber => "Number in Series",
+            FormField::Isbn => "ISBN",
+            FormField::Publisher => "Publisher",
+            FormField::Year => "Year",
+            FormField::Language => "Language",
+            FormField::Genres => "Genres",
+            FormField::IdleMinutes => "Idle After",
+        }
+    }
+
+    fn hint(self, unit: ProgressUnit) -> &'static str {
+        match self {
+            FormField::Title => "Type title",
+            FormField::Unit => "Space: pages, percent, e-reader locations or audiobook",
+            FormField::Total => match unit {
+                ProgressUnit::Pages => "Type number of pages",
+                ProgressUnit::Percent => "Percent is always out of 100",
+                ProgressUnit::Locations => "Type number of locations",
+                ProgressUnit::Audio => "Type runtime as h:mm:ss",
+            },
+            FormField::Range => "Part that counts, e.g. 15-412 to skip front matter (optional)",
+            FormField::Authors => "Authors, separated by commas (optional)",
+            FormField::Series => "Series name (optional)",
+            FormField::SeriesNumber => "Position in the series, e.g. 3, or 3/7 if the series has 7 books",
+            FormField::Isbn => "ISBN-10 or ISBN-13 (optional)",
+            FormField::Publisher => "Publisher (optional)",
+            FormField::Year => "Publication year (optional)",
+            FormField::Language => "Language (optional)",
+            FormField::Genres => "Genres or tags, separated by commas (optional)",
+            FormField::IdleMinutes => "Minutes without a key before asking if you are still reading; 0 is off, blank uses the default",
+        }
+    }
+
+    fn index(self) -> usize {
+        Self::ALL.iter().position(|&f| f == self).unwrap_or(0)
+    }
+}
+
+/// Text typed into the add/edit form
+pub struct BookForm {
+    state: FormState,
+    unit: ProgressUnit,
+    /// Unit the Range text was written in, and the book's length in it
+    range_unit: ProgressUnit,
+    range_total: usize,
+}
+
+impl Default for BookForm {
+    fn default() -> Self {
+        Self {
+            state: FormState::new(FormField::ALL.len()),
+            unit: ProgressUnit::default(),
+            range_unit: ProgressUnit::default(),
+            range_total: 0,
+        }
+    }
+}
+
+impl BookForm {
+    fn from_book(book: &BookProgress) -> Self {
+        let meta = &book.metadata;
+        let mut form = Self::default();
+        form.set_text(FormField::Title, book.book_title.clone());
+        form.unit = book.unit;
+        form.range_unit = book.unit;
+        form.range_total = book.total_pages;
+        form.set_text(FormField::Total, book.unit.format_position(book.total_pages));
+        let range = book.reading_range();
+        if range.is_partial(book.total_pages) {
+            form.set_text(
+                FormField::Range,
+                format!(
+                    "{}-{}",
+                    book.unit.format_position(range.first),
+                    book.unit.format_position(range.last)
+                ),
+            );
+        }
+        form.set_text(FormField::Authors, meta.authors.join(", "));
+        if let Some(series) = &meta.series {
+            form.set_text(FormField::Series, series.name.clone());
+            let number = match series.total {
+                Some(total) => format!("{}/{}", series.number, total),
+                None => series.number.to_string(),
+            };
+            form.set_text(FormField::SeriesNumber, number);
+        }
+        form.set_text(FormField::Isbn, meta.isbn.clone().unwrap_or_default());
+        form.set_text(FormField::Publisher, meta.publisher.clone().unwrap_or_default());
+        form.set_text(FormField::Year, meta.year.map(|y| y.to_string()).unwrap_or_default());
+        form.set_text(FormField::Language, meta.language.clone().unwrap_or_default());
+        form.set_text(FormField::Genres, meta.genres.join(", "));
+        form.set_text(
+            FormField::IdleMinutes,
+            book.idle_minutes.map(|m| m.to_string()).unwrap_or_default(),
+        );
+        form
+    }
+
+    /// An empty form for the book after `series`, with the same authors
+    fn next_in_series(series: &Series, authors: &[String]) -> Self {
+        let mut form = Self::default();
+        form.set_text(FormField::Authors, authors.join(", "));
+        form.set_text(FormField::Series, series.name.clone());
+        let number = match series.total {
+            Some(total) => format!("{}/{}", series.number + 1, total),
+            None => (series.number + 1).to_string(),
+        };
+        form.set_text(FormField::SeriesNumber, number);
+        form
+    }
+
+    /// A series name with `number` or `number/total`, or neither
+    fn parse_series(&self) -> Result<Option<Series>, (FormField, String)> {
+        let name = optional(self.text(FormField::Series));
+        let number = self.text(FormField::SeriesNumber).trim();
+        let name = match (name, number.is_empty()) {
+            (None, true) => return Ok(None),
+            (None, false) => {
+                return Err((FormField::Series, "Series name is required with a number".to_string()));
+            }
+            (Some(_), true) => {
+                return Err((FormField::SeriesNumber, "Number in series is required".to_string()));
+            }
+            (Some(name), false) => name,
+        };
+
+        let invalid = || (FormField::SeriesNumber, "Number must look like 3 or 3/7".to_string());
+        let (number, total) = match number.split_once('/') {
+            Some((number, total)) => (number, Some(total.trim().parse::<u32>().map_err(|_| invalid())?)),
+            None => (number, None),
+        };
+        let number = number.trim().parse::<u32>().map_err(|_| invalid())?;
+        if number == 0 {
+            return Err((FormField::SeriesNumber, "Series numbers start at 1".to_string()));
+        }
+        if total.is_some_and(|total| total < number) {
+            return Err((FormField::SeriesNumber, "Number is past the end of the series".to_string()));
+        }
+
+        Ok(Some(Series { name, number, total }))
+    }
+
+    /// `start-end`, `start-` or blank for the whole book
+    fn parse_range(&self, total: usize) -> Result<(usize, Option<usize>), (FormField, String)> {
+        let range = self.range_text();
+        let text = range.trim();
+        if text.is_empty() {
+            return Ok((0, None));
+        }
+
+        let invalid = || (FormField::Range, "Range must look like 15-412".to_string());
+        let (start, end) = text.split_once('-').ok_or_else(invalid)?;
+        let position = |part: &str| -> Result<Option<usize>, (FormField, String)> {
+            if part.trim().is_empty() {
+                Ok(None)
+            } else {
+                self.unit.parse_position(part).map(Some).ok_or_else(invalid)
+            }
+        };
+
+        let first = position(start)?.unwrap_or(0);
+        let last = position(end)?.unwrap_or(total);
+        if last > total {
+            return Err((FormField::Range, "Range ends after the end of the book".to_string()));
+        }
+        if first >= last {
+            return Err((FormField::Range, "Range must start before it ends".to_string()));
+        }
+
+        Ok((first, (last < total).then_some(last)))
+    }
+
+    /// What the form shows for `field`
+    fn value(&self, field: FormField) -> Cow<'_, str> {
+        match (field, self.unit) {
+            (FormField::Unit, unit) => unit.label().into(),
+            (FormField::Total, ProgressUnit::Percent) => "100".into(),
+            (FormField::Range, _) => self.range_text(),
+            _ => self.text_at(field.index()).into(),
+        }
+    }
+
+    /// The Range text in the current unit
+    ///
+    /// A range typed before Space switched the unit covers the same part of the
+    /// book, measured against the new total once that is filled in.
+    fn range_text(&self) -> Cow<'_, str> {
+        let text = self.text_at(FormField::Range.index());
+        let total = self.unit.parse_total(self.text(FormField::Total)).unwrap_or(0);
+        if self.range_unit == self.unit || self.range_total == 0 || total == 0 {
+            return text.into();
+        }
+        let convert = |part: &str| {
+            let position = self.range_unit.parse_position(part)?;
+            let fraction = position as f64 / self.range_total as f64;
+            Some(self.unit.format_position((fraction * total as f64).round() as usize))
+        };
+        let converted = match text.trim().split_once('-') {
+            Some((first, "")) => convert(first).map(|first| format!("{}-", first)),
+            Some((first, last)) => convert(first).zip(convert(last)).map(|(first, last)| format!("{}-{}", first, last)),
+            None => None,
+        };
+        converted.map_or(text.into(), Cow::Owned)
+    }
+
+    /// Write a converted range back as typed text before it is edited
+    fn settle_range(&mut self) {
+        if self.range_unit != self.unit {
+            let text = self.range_text().into_owned();
+            self.set_text(FormField::Range, text);
+            self.range_unit = self.unit;
+        }
+    }
+
+    pub fn hint(&self) -> &'static str {
+        self.field().hint(self.unit)
+    }
+}
+
+impl FieldForm for BookForm {
+    type Field = FormField;
+    type Output = BookDetails;
+
+    fn fields(&self) -> &[FormField] {
+        &FormField::ALL
+    }
+
+    fn state(&self) -> &FormState {
+        &self.state
+    }
+
+    fn state_mut(&mut self) -> &mut FormState {
+        &mut self.state
+    }
+
+    fn before_edit(&mut self, field: FormField) {
+        if field == FormField::Range {
+            self.settle_range();
+        }
+    }
+
+    fn accept_char(&mut self, field: FormField, c: char) -> bool {
+        match field {
+            FormField::Unit => {
+                if c == ' ' {
+                    if self.range_unit == self.unit {
+                        self.range_total = self.unit.parse_total(self.text(FormField::Total)).unwrap_or(0);
+                    }
+                    self.unit = self.unit.next();
+                }
+                false
+            }
+            FormField::Total => match self.unit {
+                ProgressUnit::Percent => false,
+                ProgressUnit::Audio => c.is_ascii_digit() || c == ':',
+                _ => c.is_ascii_digit(),
+            },
+            FormField::Range => {
+                c.is_ascii_digit() || c == '-' || (c == ':' && self.unit == ProgressUnit::Audio)
+            }
+            FormField::Year | FormField::IdleMinutes => c.is_ascii_digit(),
+            FormField::SeriesNumber => c.is_ascii_digit() || c == '/',
+            _ => true,
+        }
+    }
+
+    fn parse(&self) -> Result<BookDetails, (FormField, String)> {
+        let title = self.text(FormField::Title).trim().to_string();
+        if title.is_empty() {
+            return Err((FormField::Title, "Title is required".to_string()));
+        }
+
+        let total_pages = match self.unit.parse_total(self.text(FormField::Total)) {
+            Some(total) if total > 0 => total,
+            _ => {
+                let message = match self.unit {
+                    ProgressUnit::Audio => "Runtime must be h:mm:ss and longer than zero",
+                    _ => "Total must be a positive number",
+                };
+                return Err((FormField::Total, message.to_string()));
+            }
+        };
+
+        let (first_page, last_page) = self.parse_range(total_pages)?;
+
+        let isbn = match optional(self.text(FormField::Isbn)) {
+            Some(isbn) => Some(normalize_isbn(&isbn).map_err(|e| (FormField::Isbn, e))?),
+            None => None,
+        };
+
+        let year = match optional(self.text(FormField::Year)) {
+            Some(year) => Some(
+                year.parse::<i32>()
+                    .map_err(|_| (FormField::Year, "Year is not a number".to_string()))?,
+            ),
+            None => None,
+        };
+
+        let series = self.parse_series()?;
+
+        let idle_minutes = match optional(self.text(FormField::IdleMinutes)) {
+            Some(minutes) => Some(
+                minutes
+                    .parse::<u32>()
+                    .map_err(|_| (FormField::IdleMinutes, "Idle time is not a number".to_string()))?,
+            ),
+            None => None,
+        };
+
+        let metadata = BookMetadata {
+            authors: split_list(self.text(FormField::Authors)),
+            isbn,
+            publisher: optional(self.text(FormField::Publisher)),
+            year,
+            language: optional(self.text(FormField::Language)),
+            genres: split_list(self.text(FormField::Genres)),
+            series,
+        };
+
+        Ok(BookDetails {
+            book_title: title,
+            unit: self.unit,
+            total_pages,
+            first_page,
+            last_page,
+            idle_minutes,
+            metadata,
+        })
+    }
+}
+
+fn optional(value: &str) -> Option<String> {
+    let value = value.trim();
+    (!value.is_empty()).then(|| value.to_string())
+}
+
+/// Comma-separated entries, trimmed, blanks dropped
+fn split_list(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(str::to_string)
+        .collect()
 }
 
 impl BookSelector {
@@ -26,24 +508,103 @@ impl BookSelector {
         Self {
             selected_index: 0,
             input_mode: InputMode::Selection,
-            new_book_title: String::new(),
-            new_book_pages: String::new(),
+            form: BookForm::default(),
+            filter: StatusFilter::All,
+            sort: SortOrder::Status,
+            abandon_reason: String::new(),
+            read_only: false,
+        }
+    }
+
+    /// Books passing the filter, in list order
+    pub fn visible_books<'a>(&self, library: &'a Library) -> Vec<&'a BookProgress> {
+        let mut books: Vec<&BookProgress> = library
+            .books
+            .iter()
+            .filter(|b| self.filter.matches(&b.status))
+            .collect();
+        match self.sort {
+            SortOrder::Status => books.sort_by_key(|b| b.status.rank()),
+            SortOrder::Rating => books.sort_by(|a, b| {
+                b.rating.unwrap_or(0.0).total_cmp(&a.rating.unwrap_or(0.0))
+            }),
+            SortOrder::Series => books.sort_by_key(|b| match &b.metadata.series {
+                Some(series) => (false, series.name.to_lowercase(), series.number, b.status.rank()),
+                None => (true, String::new(), 0, b.status.rank()),
+            }),
+        }
+        books
+    }
+
+    pub fn toggle_sort(&mut self, library: &Library) {
+        let selected = self.selected_book(library).map(|b| b.id);
+        self.sort = self.sort.next();
+        if let Some(book_id) = selected {
+            self.select_book(library, book_id);
         }
     }
 
     pub fn select_next(&mut self, library: &Library) {
-        if library.books.is_empty() {
+        let count = self.visible_books(library).len();
+        if count == 0 {
             return;
         }
-        self.selected_index = (self.selected_index + 1) % library.books.len();
+        self.selected_index = (self.selected_index + 1) % count;
+    }
+
+    /// Select the book if it is visible, otherwise keep the selection in range
+    pub fn select_book(&mut self, library: &Library, book_id: BookId) {
+        match self.visible_books(library).iter().position(|b| b.id == book_id) {
+            Some(index) => self.selected_index = index,
+            None => self.clamp_selection(library),
+        }
+    }
+
+    pub fn selected_book<'a>(&self, library: &'a Library) -> Option<&'a BookProgress> {
+        self.visible_books(library).get(self.selected_index).copied()
+    }
+
+    /// Keep the selection inside the list, e.g. after a book was deleted
+    pub fn clamp_selection(&mut self, library: &Library) {
+        let count = self.visible_books(library).len();
+        self.selected_index = self.selected_index.min(count.saturating_sub(1));
+    }
+
+    pub fn cycle_filter(&mut self, library: &Library) {
+        self.filter = self.filter.next();
+        self.selected_index = 0;
+        self.clamp_selection(library);
+    }
+
+    pub fn start_abandon(&mut self, book_id: BookId) {
+        self.input_mode = InputMode::AbandonReason(book_id);
+        self.abandon_reason.clear();
+    }
+
+    pub fn start_add(&mut self) {
+        self.input_mode = InputMode::AddingBook;
+        self.form = BookForm::default();
+    }
+
+    /// Open the add form for the book after `series`
+    pub fn start_add_next_in_series(&mut self, series: &Series, authors: &[String]) {
+        self.input_mode = InputMode::AddingBook;
+        self.form = BookForm::next_in_series(series, authors);
+    }
+
+    /// Open the form pre-filled with the book's current details
+    pub fn start_edit(&mut self, book: &BookProgress) {
+        self.input_mode = InputMode::EditingBook(book.id);
+        self.form = BookForm::from_book(book);
     }
 
     pub fn select_prev(&mut self, library: &Library) {
-        if library.books.is_empty() {
+        let count = self.visible_books(library).len();
+        if count == 0 {
             return;
         }
         if self.selected_index == 0 {
-            self.selected_index = library.books.len() - 1;
+            self.selected_index = count - 1;
         } else {
             self.selected_index -= 1;
         }
@@ -51,103 +612,259 @@ impl BookSelector {
 }
 
 pub fn draw_book_select(f: &mut Frame, library: &Library, selector: &BookSelector) {
+    let form_open = matches!(
+        selector.input_mode,
+        InputMode::AddingBook | InputMode::EditingBook(_)
+    );
+    // Every field plus the error line, inside a border and margin
+    let form_height = if form_open { FormField::ALL.len() as u16 + 5 } else { 0 };
+
     let chunks = Layout::default()
         .direction(Direction::Vertical)
         .margin(2)
         .constraints([
-            Constraint::Length(3),  // Title
-            Constraint::Min(5),     // Book list
-            Constraint::Length(8),  // New book input
-            Constraint::Length(4),  // Controls
+            Constraint::Length(3),           // Title
+            Constraint::Min(5),              // Book list
+            Constraint::Length(form_height), // Book form
+            Constraint::Length(4),           // Controls
         ])
         .split(f.area());
 
     // Title
-    let title = Paragraph::new("Book Library")
-        .style(Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD))
+    let (title_text, title_color) = if selector.read_only {
+        ("Book Library (read-only)", Color::Yellow)
+    } else {
+        ("Book Library", Color::Cyan)
+    };
+    let title = Paragraph::new(title_text)
+        .style(Style::default().fg(title_color).add_modifier(Modifier::BOLD))
         .alignment(Alignment::Center)
         .block(Block::default().borders(Borders::ALL));
     f.render_widget(title, chunks[0]);
 
-    // Book list
-    let books: Vec<ListItem> = library
-        .books
-        .iter()
-        .enumerate()
-        .map(|(i, book)| {
-            let progress = if book.total_pages > 0 {
-                (book.current_page as f64 / book.total_pages as f64 * 100.0) as u32
-            } else {
-                0
-            };
-
-            let content = format!(
-                "{} - {}/{} pages ({}%)",
-                book.book_title, book.current_page, book.total_pages, progress
+    // Book list, with a heading above each status or series group
+    let visible = selector.visible_books(library);
+    let mut books: Vec<ListItem> = Vec::new();
+    for (i, book) in visible.iter().enumerate() {
+        if let Some(group) = selector.sort.group(book)
+            && (i == 0 || selector.sort.group(visible[i - 1]) != Some(group))
+        {
+            books.push(
+                ListItem::new(group.to_string())
+                    .style(Style::default().fg(Color::Cyan).add_modifier(Modifier::UNDERLINED)),
             );
+        }
 
-            let style = if i == selector.selected_index {
-                Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)
-            } else {
-                Style::default().fg(Color::White)
-            };
+        let progress = (book.reading_range().progress(book.current_page) * 100.0) as u32;
 
-            ListItem::new(content).style(style)
-        })
-        .collect();
+        let byline = if book.metadata.authors.is_empty() {
+            String::new()
+        } else {
+            format!(" by {}", book.metadata.authors.join(", "))
+        };
 
-    let book_list = List::new(books)
-        .block(Block::default().borders(Borders::ALL).title("Books"))
-        .highlight_style(Style::default().add_modifier(Modifier::BOLD));
+        let status_note = match (&book.status, book.finished_at) {
+            (ReadingStatus::Finished, Some(at)) => format!(" - finished {}", at.format("%Y-%m-%d")),
+            (ReadingStatus::Abandoned { reason }, _) if !reason.is_empty() => {
+                format!(" - DNF: {}", reason)
+            }
+            _ => String::new(),
+        };
 
-    f.render_widget(book_list, chunks[1]);
+        let read_note = if book.read_number() > 1 {
+            format!(" [Read #{}]", book.read_number())
+        } else {
+            String::new()
+        };
 
-    // New book input
-    let (title_color, pages_color) = match selector.input_mode {
-        InputMode::EnteringTitle => (Color::Yellow, Color::White),
-        InputMode::EnteringPages => (Color::White, Color::Yellow),
-        InputMode::Selection => (Color::White, Color::White),
-    };
+        let position = match book.unit {
+            ProgressUnit::Percent => book.unit.format_fraction(book.current_page, book.total_pages),
+            unit => format!(
+                "{} ({}%)",
+                unit.format_fraction(book.current_page, book.total_pages),
+                progress
+            ),
+        };
 
-    let new_book_block = Block::default().borders(Borders::ALL).title("Add New Book");
-    let inner = new_book_block.inner(chunks[2]);
-    f.render_widget(new_book_block, chunks[2]);
+        let series_note = match (&book.metadata.series, selector.sort) {
+            (Some(series), SortOrder::Series) => format!(" (#{})", series.number),
+            (Some(series), _) => format!(" ({} #{})", series.name, series.number),
+            (None, _) => String::new(),
+        };
 
-    let input_chunks = Layout::default()
-        .direction(Direction::Vertical)
-        .margin(1)
-        .constraints([
-            Constraint::Length(1),
-            Constraint::Length(1),
-            Constraint::Length(1),
-            Constraint::Length(1),
-        ])
-        .split(inner);
+        let stars = book.rating.map(|r| format!(" {}", format_stars(r))).unwrap_or_default();
 
-    let title_input = Paragraph::new(format!("Title: {}", selector.new_book_title))
-        .style(Style::default().fg(title_color));
-    f.render_widget(title_input, input_chunks[0]);
+        let content = format!(
+            "  {}{}{}{}{} - {}{}",
+            book.book_title, series_note, byline, read_note, stars, position, status_note
+        );
 
-    let pages_input = Paragraph::new(format!("Total Pages: {}", selector.new_book_pages))
-        .style(Style::default().fg(pages_color));
-    f.render_widget(pages_input, input_chunks[1]);
+        let style = if i == selector.selected_index {
+            Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)
+        } else {
+            Style::default().fg(Color::White)
+        };
+
+        books.push(ListItem::new(content).style(style));
+    }
+
+    let book_list = List::new(books)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(match selector.sort {
+                    SortOrder::Status => format!("Books ({})", selector.filter.label()),
+                    SortOrder::Rating => format!("Books ({}, by rating)", selector.filter.label()),
+                    SortOrder::Series => format!("Books ({}, by series)", selector.filter.label()),
+                }),
+        )
+        .highlight_style(Style::default().add_modifier(Modifier::BOLD));
+
+    f.render_widget(book_list, chunks[1]);
+
+    // Book form
+    if form_open {
+        draw_book_form(f, selector, chunks[2]);
+    }
 
     // Controls
     let controls = match selector.input_mode {
-        InputMode::Selection => {
-            if library.books.is_empty() {
-                "N: New Book | Q: Quit"
-            } else {
-                "↑/↓: Select | Enter: Open Book | N: New Book | Q: Quit"
+        InputMode::Selection => match selector.selected_book(library) {
+            None => "N: New Book | Tab: Filter | Q: Quit".to_string(),
+            Some(book) => {
+                let abandon = if matches!(book.status, ReadingStatus::Abandoned { .. }) {
+                    "Resume"
+                } else {
+                    "Abandon (DNF)"
+                };
+                let reread = match book.status {
+                    ReadingStatus::Finished | ReadingStatus::Abandoned { .. } => " | R: Read Again",
+                    _ => "",
+                };
+                format!(
+                    "↑/↓: Select | Enter: Open Book | N: New Book | E: Edit | D: Delete | C: Chapters | H: History | L: Log Session | A: {}{} | /: Search Notes | S: Sort | T: Stats | Tab: Filter | Q: Quit",
+                    abandon, reread
+                )
             }
+        },
+        InputMode::AddingBook | InputMode::EditingBook(_) => {
+            let enter = if selector.form.on_last_field() { "Save" } else { "Next" };
+            format!(
+                "{} | Enter: {} | Tab/↑/↓: Move | Esc: Cancel",
+                selector.form.hint(),
+                enter
+            )
+        }
+        InputMode::ConfirmDelete(_) => "Y: Delete | N/Esc: Cancel".to_string(),
+        InputMode::AbandonReason(_) => {
+            format!("Why did you stop? {}_ | Enter: Abandon | Esc: Cancel", selector.abandon_reason)
         }
-        InputMode::EnteringTitle => "Type title, then Enter",
-        InputMode::EnteringPages => "Type number of pages, then Enter",
     };
 
     let controls_widget = Paragraph::new(controls)
         .style(Style::default().fg(Color::Cyan))
         .alignment(Alignment::Center)
+        .wrap(Wrap { trim: true })
         .block(Block::default().borders(Borders::ALL).title("Controls"));
     f.render_widget(controls_widget, chunks[3]);
+
+    if let InputMode::ConfirmDelete(book_id) = selector.input_mode
+        && let Some(book) = library.books.iter().find(|b| b.id == book_id)
+    {
+        draw_delete_confirmation(f, book);
+    }
+}
+
+fn draw_book_form(f: &mut Frame, selector: &BookSelector, area: Rect) {
+    let form_title = match selector.input_mode {
+        InputMode::EditingBook(_) => "Edit Book",
+        _ => "Add New Book",
+    };
+    let form_block = Block::default().borders(Borders::ALL).title(form_title);
+    let inner = form_block.inner(area);
+    f.render_widget(form_block, area);
+
+    let form = &selector.form;
+    let mut lines: Vec<Line> = FormField::ALL
+        .iter()
+        .enumerate()
+        .map(|(i, &field)| {
+            let color = if i == form.focus() { Color::Yellow } else { Color::White };
+            Line::from(Span::styled(
+                format!("{}: {}", field.label(form.unit), form.value(field)),
+                Style::default().fg(color),
+            ))
+        })
+        .collect();
+
+    if let Some(error) = form.error() {
+        lines.push(Line::from(Span::styled(
+            error.to_string(),
+            Style::default().fg(Color::Red),
+        )));
+    }
+
+    let input_area = Layout::default()
+        .direction(Direction::Vertical)
+        .margin(1)
+        .constraints([Constraint::Min(0)])
+        .split(inner)[0];
+    f.render_widget(Paragraph::new(lines), input_area);
+}
+
+fn draw_delete_confirmation(f: &mut Frame, book: &BookProgress) {
+    let area = centered_rect(50, 7, f.area());
+
+    let text = vec![
+        Line::from(vec![
+            Span::raw("Delete "),
+            Span::styled(
+                book.book_title.clone(),
+                Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD),
+            ),
+            Span::raw("?"),
+        ]),
+        Line::from(format!(
+            "Its {} reading sessions will be removed too.",
+            book.all_sessions().count()
+        )),
+        Line::from(""),
+        Line::from(Span::styled("Y: Delete | N: Cancel", Style::default().fg(Color::Cyan))),
+    ];
+
+    let dialog = Paragraph::new(text)
+        .alignment(Alignment::Center)
+        .wrap(Wrap { trim: true })
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title("Confirm Delete")
+                .border_style(Style::default().fg(Color::Red)),
+        );
+
+    f.render_widget(Clear, area);
+    f.render_widget(dialog, area);
+}
+
+/// Half-star rating as five symbols, e.g. ★★★½☆
+pub fn format_stars(rating: f32) -> String {
+    let half_stars = (rating * 2.0).round() as usize;
+    let mut stars = "★".repeat(half_stars / 2);
+    if half_stars % 2 == 1 {
+        stars.push('½');
+    }
+    let shown = half_stars / 2 + half_stars % 2;
+    stars.push_str(&"☆".repeat(5usize.saturating_sub(shown)));
+    stars
+}
+
+/// A rectangle `percent_x` wide and `height` rows tall in the middle of `area`
+pub fn centered_rect(percent_x: u16, height: u16, area: Rect) -> Rect {
+    let width = area.width * percent_x / 100;
+    Rect {
+        x: area.x + (area.width.saturating_sub(width)) / 2,
+        y: area.y + (area.height.saturating_sub(height)) / 2,
+        width,
+        height: height.min(area.height),
+    }
 }
@@ -0,0 +1,231 @@
+use ratatui::{
+    layout::{Alignment, Constraint, Direction, Layout},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, List, ListItem, Paragraph},
+    Frame,
+};
+use std::fs;
+use std::path::Path;
+
+use crate::storage::{BookId, BookProgress, Chapter, ProgressUnit};
+
+/// Table of contents editor for one book
+pub struct ChapterEditor {
+    pub book_id: BookId,
+    pub book_title: String,
+    pub unit: ProgressUnit,
+    pub chapters: Vec<Chapter>,
+    pub selected_index: usize,
+    pub input_mode: ChapterInput,
+    pub input: String,
+    /// Why the last entry or import was rejected
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ChapterInput {
+    Browse,
+    Adding,
+    Importing,
+}
+
+impl ChapterEditor {
+    pub fn new(book: &BookProgress) -> Self {
+        Self {
+            book_id: book.id,
+            book_title: book.book_title.clone(),
+            unit: book.unit,
+            chapters: book.chapters.clone(),
+            selected_index: 0,
+            input_mode: ChapterInput::Browse,
+            input: String::new(),
+            error: None,
+        }
+    }
+
+    pub fn select_next(&mut self) {
+        if !self.chapters.is_empty() {
+            self.selected_index = (self.selected_index + 1) % self.chapters.len();
+        }
+    }
+
+    pub fn select_prev(&mut self) {
+        if !self.chapters.is_empty() {
+            self.selected_index = self
+                .selected_index
+                .checked_sub(1)
+                .unwrap_or(self.chapters.len() - 1);
+        }
+    }
+
+    pub fn start_input(&mut self, mode: ChapterInput) {
+        self.input_mode = mode;
+        self.input.clear();
+        self.error = None;
+    }
+
+    pub fn delete_selected(&mut self) {
+        if self.selected_index < self.chapters.len() {
+            self.chapters.remove(self.selected_index);
+            self.selected_index = self.selected_index.min(self.chapters.len().saturating_sub(1));
+        }
+    }
+
+    /// Add the typed chapter; returns whether the chapter list changed
+    pub fn submit_chapter(&mut self) -> bool {
+        match parse_chapter_line(&self.input, self.unit) {
+            Ok(Some(chapter)) => {
+                let index = self.chapters.partition_point(|c| c.start_page <= chapter.start_page);
+                self.chapters.insert(index, chapter);
+                self.selected_index = index;
+                self.input_mode = ChapterInput::Browse;
+                self.error = None;
+                true
+            }
+            Ok(None) => {
+                self.input_mode = ChapterInput::Browse;
+                false
+            }
+            Err(e) => {
+                self.error = Some(e);
+                false
+            }
+        }
+    }
+
+    /// Replace the chapters with those in the typed file; returns whether they changed
+    pub fn submit_import(&mut self) -> bool {
+        match import_chapters(Path::new(self.input.trim()), self.unit) {
+            Ok(chapters) => {
+                self.chapters = chapters;
+                self.selected_index = 0;
+                self.input_mode = ChapterInput::Browse;
+                self.error = None;
+                true
+            }
+            Err(e) => {
+                self.error = Some(e);
+                false
+            }
+        }
+    }
+}
+
+/// Parse `15 Chapter Two` or a table-of-contents line like `Chapter Two .... 15`
+///
+/// Blank lines and lines starting with `#` yield `None`.
+pub fn parse_chapter_line(line: &str, unit: ProgressUnit) -> Result<Option<Chapter>, String> {
+    let line = line.trim();
+    if line.is_empty() || line.starts_with('#') {
+        return Ok(None);
+    }
+
+    let leading = line
+        .split_once(char::is_whitespace)
+        .and_then(|(first, rest)| Some((unit.parse_position(first)?, rest)));
+    let trailing = line
+        .rsplit_once(char::is_whitespace)
+        .and_then(|(rest, last)| Some((unit.parse_position(last)?, rest)));
+
+    let (start_page, title) = leading
+        .or(trailing)
+        .ok_or_else(|| format!("\"{}\" needs a starting position and a title", line))?;
+    let title = title.trim().trim_end_matches(['.', ' ', '\t']).trim();
+    if title.is_empty() {
+        return Err(format!("\"{}\" has no chapter title", line));
+    }
+
+    Ok(Some(Chapter {
+        title: title.to_string(),
+        start_page,
+    }))
+}
+
+/// Read a table of contents from a text file, one chapter per line
+fn import_chapters(path: &Path, unit: ProgressUnit) -> Result<Vec<Chapter>, String> {
+    let text = fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+
+    let mut chapters = Vec::new();
+    for (number, line) in text.lines().enumerate() {
+        let chapter = parse_chapter_line(line, unit).map_err(|e| format!("line {}: {}", number + 1, e))?;
+        chapters.extend(chapter);
+    }
+    if chapters.is_empty() {
+        return Err(format!("{} has no chapters", path.display()));
+    }
+
+    chapters.sort_by_key(|c| c.start_page);
+    Ok(chapters)
+}
+
+pub fn draw_chapters(f: &mut Frame, editor: &ChapterEditor) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .margin(2)
+        .constraints([
+            Constraint::Length(3), // Title
+            Constraint::Min(5),    // Chapter list
+            Constraint::Length(4), // Input
+            Constraint::Length(3), // Controls
+        ])
+        .split(f.area());
+
+    // Title
+    let title = Paragraph::new(format!("Chapters: {}", editor.book_title))
+        .style(Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD))
+        .alignment(Alignment::Center)
+        .block(Block::default().borders(Borders::ALL));
+    f.render_widget(title, chunks[0]);
+
+    // Chapter list
+    let items: Vec<ListItem> = if editor.chapters.is_empty() {
+        vec![ListItem::new("No chapters yet").style(Style::default().fg(Color::Gray))]
+    } else {
+        editor
+            .chapters
+            .iter()
+            .enumerate()
+            .map(|(i, chapter)| {
+                let style = if i == editor.selected_index {
+                    Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)
+                } else {
+                    Style::default().fg(Color::White)
+                };
+                ListItem::new(format!(
+                    "{:>8}  {}",
+                    editor.unit.format_position(chapter.start_page),
+                    chapter.title
+                ))
+                .style(style)
+            })
+            .collect()
+    };
+    let list = List::new(items).block(Block::default().borders(Borders::ALL).title("Contents"));
+    f.render_widget(list, chunks[1]);
+
+    // Input
+    let prompt = match editor.input_mode {
+        ChapterInput::Browse => String::new(),
+        ChapterInput::Adding => format!("Chapter: {}", editor.input),
+        ChapterInput::Importing => format!("File: {}", editor.input),
+    };
+    let mut lines = vec![Line::from(Span::styled(prompt, Style::default().fg(Color::Yellow)))];
+    if let Some(error) = &editor.error {
+        lines.push(Line::from(Span::styled(error.clone(), Style::default().fg(Color::Red))));
+    }
+    let input = Paragraph::new(lines).block(Block::default().borders(Borders::ALL));
+    f.render_widget(input, chunks[2]);
+
+    // Controls
+    let controls = match editor.input_mode {
+        ChapterInput::Browse => "↑/↓: Select | A: Add | D: Delete | I: Import File | Esc: Back",
+        ChapterInput::Adding => "Type start and title, e.g. 15 Chapter Two | Enter: Add | Esc: Cancel",
+        ChapterInput::Importing => "Path to a text file, one chapter per line | Enter: Import | Esc: Cancel",
+    };
+    let controls_widget = Paragraph::new(controls)
+        .style(Style::default().fg(Color::Cyan))
+        .alignment(Alignment::Center)
+        .block(Block::default().borders(Borders::ALL).title("Controls"));
+    f.render_widget(controls_widget, chunks[3]);
+}
@@ -0,0 +1,123 @@
+use chrono::{DateTime, Duration as ChronoDuration, Local};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use uuid::Uuid;
+
+use crate::storage::{chapters_covered, write_atomic, BookId, BookProgress, ReadingSession, TimeInterval};
+
+const CHECKPOINT_FILE: &str = "active_session.json";
+
+/// How often a running timer is written to disk
+const CHECKPOINT_INTERVAL: Duration = Duration::from_secs(15);
+
+/// The timer's unsaved session, as of its last checkpoint
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ActiveSession {
+    pub book_id: BookId,
+    pub session_id: Uuid,
+    pub session_start_page: usize,
+    pub current_page: usize,
+    /// Time on the timer when the checkpoint was taken
+    pub elapsed_secs: u64,
+    /// Whether the timer was running rather than paused
+    pub running: bool,
+    /// Stretches the timer ran, the last one ending at the checkpoint if it was running
+    #[serde(default)]
+    pub intervals: Vec<TimeInterval>,
+    /// Focus-mode blocks completed so far
+    #[serde(default)]
+    pub focus_blocks: u32,
+    pub checkpointed_at: DateTime<Local>,
+}
+
+impl ActiveSession {
+    /// The session as it stood at the checkpoint, ending when the timer last ran
+    pub fn to_session(&self, book: &BookProgress) -> ReadingSession {
+        ReadingSession {
+            id: self.session_id,
+            book_title: book.book_title.clone(),
+            start_page: self.session_start_page,
+            end_page: self.current_page,
+            unit: book.unit,
+            duration_secs: self.elapsed_secs,
+            started_at: self.started_at(),
+            timestamp: self.intervals.last().map_or(self.checkpointed_at, |i| i.end),
+            intervals: if self.intervals.is_empty() {
+                vec![TimeInterval {
+                    start: self.started_at(),
+                    end: self.checkpointed_at,
+                }]
+            } else {
+                self.intervals.clone()
+            },
+            focus_blocks: self.focus_blocks,
+            chapters: chapters_covered(&book.chapters, self.session_start_page, self.current_page),
+        }
+    }
+
+    /// When the timer was first started, as far as the checkpoint knows
+    pub fn started_at(&self) -> DateTime<Local> {
+        self.intervals.first().map_or_else(
+            || self.checkpointed_at - ChronoDuration::seconds(self.elapsed_secs as i64),
+            |i| i.start,
+        )
+    }
+}
+
+/// The session left behind by a run that ended without stopping its timer
+pub fn load_checkpoint(dir: &Path) -> Option<ActiveSession> {
+    let data = fs::read_to_string(dir.join(CHECKPOINT_FILE)).ok()?;
+    serde_json::from_str(&data).ok()
+}
+
+/// Keeps `active_session.json` in step with the timer
+///
+/// The file is rewritten every `CHECKPOINT_INTERVAL` while a session is open,
+/// and straight away when the timer is paused or resumed or the page moves.
+/// It is removed once the session is saved or discarded.
+pub struct Checkpointer {
+    path: PathBuf,
+    /// When the file was last written, and the timer state and page it recorded
+    last: Option<(Instant, bool, usize)>,
+}
+
+impl Checkpointer {
+    pub fn new(dir: &Path) -> Self {
+        Self {
+            path: dir.join(CHECKPOINT_FILE),
+            last: None,
+        }
+    }
+
+    pub fn update(&mut self, active: Option<ActiveSession>) -> std::io::Result<()> {
+        let Some(active) = active else {
+            return self.clear();
+        };
+
+        let due = match self.last {
+            Some((written, running, page)) => {
+                written.elapsed() >= CHECKPOINT_INTERVAL
+                    || running != active.running
+                    || page != active.current_page
+            }
+            None => true,
+        };
+        if due {
+            write_atomic(&self.path, serde_json::to_string_pretty(&active)?.as_bytes())?;
+            self.last = Some((Instant::now(), active.running, active.current_page));
+        }
+        Ok(())
+    }
+
+    /// Remove the checkpoint, e.g. once its session was saved or discarded
+    pub fn clear(&mut self) -> std::io::Result<()> {
+        self.last = None;
+        match fs::remove_file(&self.path) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+            _ => Ok(()),
+        }
+    }
+}
@@ -0,0 +1,61 @@
+use std::path::PathBuf;
+
+use crate::config::BackendKind;
+
+pub const USAGE: &str = "Usage: book_garden [--data-dir <path>]
+       book_garden migrate-storage --from <backend> --to <backend> [--data-dir <path>]
+
+Commands:
+  migrate-storage    Copy the library between storage backends (json, sqlite)
+
+Options:
+  --data-dir <path>  Directory holding the library (overrides BOOK_GARDEN_DATA_DIR)
+  -h, --help         Show this help";
+
+#[derive(Debug)]
+pub enum Command {
+    MigrateStorage { from: BackendKind, to: BackendKind },
+}
+
+#[derive(Debug, Default)]
+pub struct Args {
+    pub data_dir: Option<PathBuf>,
+    pub help: bool,
+    pub command: Option<Command>,
+}
+
+/// Parse command line arguments (excluding the program name)
+pub fn parse_args(args: impl IntoIterator<Item = String>) -> Result<Args, String> {
+    let mut parsed = Args::default();
+    let mut args = args.into_iter();
+    let mut migrate = false;
+    let mut from = None;
+    let mut to = None;
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--data-dir" => {
+                let value = args.next().ok_or("--data-dir requires a path")?;
+                parsed.data_dir = Some(PathBuf::from(value));
+            }
+            "--from" => from = Some(args.next().ok_or("--from requires a backend")?.parse()?),
+            "--to" => to = Some(args.next().ok_or("--to requires a backend")?.parse()?),
+            "migrate-storage" => migrate = true,
+            "-h" | "--help" => parsed.help = true,
+            _ => match arg.strip_prefix("--data-dir=") {
+                Some(value) => parsed.data_dir = Some(PathBuf::from(value)),
+                None => return Err(format!("unrecognized argument '{}'", arg)),
+            },
+        }
+    }
+
+    if migrate {
+        let from = from.ok_or("migrate-storage requires --from")?;
+        let to = to.ok_or("migrate-storage requires --to")?;
+        parsed.command = Some(Command::MigrateStorage { from, to });
+    } else if from.is_some() || to.is_some() {
+        return Err("--from and --to are only valid with migrate-storage".to_string());
+    }
+
+    Ok(parsed)
+}
@@ -0,0 +1,106 @@
+use serde::{Deserialize, Serialize};
+use std::env;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+const CONFIG_FILE: &str = "config.json";
+const DATA_DIR_ENV: &str = "BOOK_GARDEN_DATA_DIR";
+
+/// Where the library is persisted
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum BackendKind {
+    /// A single `library.json` document
+    #[default]
+    Json,
+    /// An embedded `library.sqlite3` database
+    Sqlite,
+}
+
+impl fmt::Display for BackendKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BackendKind::Json => write!(f, "json"),
+            BackendKind::Sqlite => write!(f, "sqlite"),
+        }
+    }
+}
+
+impl std::str::FromStr for BackendKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "json" => Ok(BackendKind::Json),
+            "sqlite" => Ok(BackendKind::Sqlite),
+            _ => Err(format!("unknown storage backend '{}' (expected json or sqlite)", s)),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct Config {
+    /// Number of rotating library backups to keep (0 disables backups)
+    pub backup_count: usize,
+    pub backend: BackendKind,
+    /// Minutes without a key press before a running timer asks if you are
+    /// still reading; books can override it
+    pub idle_minutes: Option<u32>,
+    /// Length of a focus-mode block of reading, and of the break after it
+    pub focus_minutes: u32,
+    pub break_minutes: u32,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            backup_count: 5,
+            backend: BackendKind::Json,
+            idle_minutes: None,
+            focus_minutes: 25,
+            break_minutes: 5,
+        }
+    }
+}
+
+/// Load the user configuration, falling back to defaults when no file exists
+pub fn load_config(dir: &Path) -> std::io::Result<Config> {
+    let path = dir.join(CONFIG_FILE);
+    if !path.exists() {
+        return Ok(Config::default());
+    }
+
+    let data = fs::read_to_string(&path)?;
+    serde_json::from_str(&data).map_err(|e| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("{} is invalid: {}", path.display(), e),
+        )
+    })
+}
+
+/// Pick the data directory: `--data-dir`, then `BOOK_GARDEN_DATA_DIR`, then the XDG default
+pub fn resolve_data_dir(flag: Option<PathBuf>) -> PathBuf {
+    flag.or_else(|| non_empty_var(DATA_DIR_ENV).map(PathBuf::from))
+        .unwrap_or_else(default_data_dir)
+}
+
+/// `$XDG_DATA_HOME/book_garden`, falling back to `~/.local/share/book_garden`
+fn default_data_dir() -> PathBuf {
+    let base = non_empty_var("XDG_DATA_HOME")
+        .map(PathBuf::from)
+        .filter(|p| p.is_absolute())
+        .or_else(|| non_empty_var("HOME").map(|home| Path::new(&home).join(".local/share")));
+
+    match base {
+        Some(base) => base.join("book_garden"),
+        None => PathBuf::from("reading_data"),
+    }
+}
+
+fn non_empty_var(name: &str) -> Option<String> {
+    env::var(name).ok().filter(|v| !v.is_empty())
+}
@@ -0,0 +1,126 @@
+/// Text typed into a `FieldForm`, which field has focus and why the last submit failed
+pub struct FormState {
+    values: Vec<String>,
+    focus: usize,
+    error: Option<String>,
+}
+
+impl FormState {
+    /// Blank text for `len` fields
+    pub fn new(len: usize) -> Self {
+        Self::with_values(vec![String::new(); len])
+    }
+
+    /// Prefilled text, one value per field in tab order
+    pub fn with_values(values: Vec<String>) -> Self {
+        Self {
+            values,
+            focus: 0,
+            error: None,
+        }
+    }
+}
+
+/// A form of text fields filled in one after another
+///
+/// Implementors list their fields, decide which characters each takes and turn
+/// the text into a value; moving between fields, editing and reporting a
+/// rejected submit work the same for every form.
+pub trait FieldForm {
+    type Field: Copy + PartialEq;
+    type Output;
+
+    /// The fields in tab order
+    fn fields(&self) -> &[Self::Field];
+    fn state(&self) -> &FormState;
+    fn state_mut(&mut self) -> &mut FormState;
+    /// Whether `c` is typed into `field`; a form may act on it instead, like Space cycling a choice
+    fn accept_char(&mut self, field: Self::Field, c: char) -> bool;
+    /// Check every field, naming the first bad one
+    fn parse(&self) -> Result<Self::Output, (Self::Field, String)>;
+
+    /// Called before `field` is typed into or erased from
+    fn before_edit(&mut self, _field: Self::Field) {}
+
+    fn field(&self) -> Self::Field {
+        self.fields()[self.state().focus]
+    }
+
+    fn focus(&self) -> usize {
+        self.state().focus
+    }
+
+    /// Why the last submit was rejected
+    fn error(&self) -> Option<&str> {
+        self.state().error.as_deref()
+    }
+
+    /// Text of the field at `index`, as typed
+    fn text_at(&self, index: usize) -> &str {
+        &self.state().values[index]
+    }
+
+    /// Text of `field` without surrounding whitespace
+    fn text(&self, field: Self::Field) -> &str {
+        self.fields()
+            .iter()
+            .position(|&f| f == field)
+            .map_or("", |i| self.state().values[i].trim())
+    }
+
+    fn set_text(&mut self, field: Self::Field, text: String) {
+        if let Some(i) = self.fields().iter().position(|&f| f == field) {
+            self.state_mut().values[i] = text;
+        }
+    }
+
+    fn on_last_field(&self) -> bool {
+        self.state().focus == self.fields().len() - 1
+    }
+
+    fn next_field(&mut self) {
+        let last = self.fields().len() - 1;
+        let state = self.state_mut();
+        state.focus = (state.focus + 1).min(last);
+    }
+
+    fn prev_field(&mut self) {
+        let state = self.state_mut();
+        state.focus = state.focus.saturating_sub(1);
+    }
+
+    fn push_char(&mut self, c: char) {
+        self.before_edit(self.field());
+        if self.accept_char(self.field(), c) {
+            let state = self.state_mut();
+            state.values[state.focus].push(c);
+        }
+    }
+
+    fn pop_char(&mut self) {
+        self.before_edit(self.field());
+        let state = self.state_mut();
+        state.values[state.focus].pop();
+    }
+
+    /// Validate every field, moving focus to the first bad one on error
+    fn submit(&mut self) -> Option<Self::Output> {
+        let result = self.parse();
+        let fields_focus = match &result {
+            Err((field, _)) => self.fields().iter().position(|f| f == field),
+            Ok(_) => None,
+        };
+        let state = self.state_mut();
+        match result {
+            Ok(output) => {
+                state.error = None;
+                Some(output)
+            }
+            Err((_, message)) => {
+                state.focus = fields_focus.unwrap_or(0);
+                state.error = Some(message);
+                None
+            }
+        }
+    }
+}
@@ -0,0 +1,432 @@
+use ratatui::{
+    layout::{Alignment, Constraint, Direction, Layout},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, List, ListItem, ListState, Paragraph},
+    Frame,
+};
+use chrono::TimeDelta;
+use uuid::Uuid;
+
+use crate::form::{FieldForm, FormState};
+use crate::session_log::parse_duration;
+use crate::storage::{format_hms, BookId, BookProgress, Library, ProgressUnit, ReadingSession};
+
+/// Fields of the edit and split forms
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SessionField {
+    Duration,
+    StartPage,
+    EndPage,
+    /// How long the first part of a split lasted
+    FirstPart,
+    SplitAt,
+}
+
+impl SessionField {
+    fn label(self, unit: ProgressUnit) -> &'static str {
+        match (self, unit) {
+            (SessionField::Duration, _) => "Duration",
+            (SessionField::StartPage, ProgressUnit::Pages) => "Start Page",
+            (SessionField::EndPage, ProgressUnit::Pages) => "End Page",
+            (SessionField::StartPage, _) => "Start Position",
+            (SessionField::EndPage, _) => "End Position",
+            (SessionField::FirstPart, _) => "First Part Lasted",
+            (SessionField::SplitAt, ProgressUnit::Pages) => "Split At Page",
+            (SessionField::SplitAt, _) => "Split At",
+        }
+    }
+
+    fn hint(self) -> &'static str {
+        match self {
+            SessionField::Duration => "Minutes, h:mm or h:mm:ss",
+            SessionField::StartPage | SessionField::EndPage => "Where the session started and ended",
+            SessionField::FirstPart => "Time before the split, in minutes, h:mm or h:mm:ss",
+            SessionField::SplitAt => "Where the first part ended and the second began",
+        }
+    }
+}
+
+const EDIT_FIELDS: [SessionField; 3] = [SessionField::Duration, SessionField::StartPage, SessionField::EndPage];
+const SPLIT_FIELDS: [SessionField; 2] = [SessionField::FirstPart, SessionField::SplitAt];
+
+/// A change to the selected session, checked and ready to save
+pub enum SessionChange {
+    Edit {
+        start_page: usize,
+        end_page: usize,
+        duration_secs: u64,
+    },
+    Split {
+        at_page: usize,
+        first_secs: u64,
+    },
+}
+
+/// What the session form changes
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum SessionMode {
+    Edit,
+    Split,
+}
+
+/// Text typed into the edit or split form
+pub struct SessionForm {
+    mode: SessionMode,
+    state: FormState,
+    session: ReadingSession,
+    /// Highest position the book has, when the session uses the book's current unit
+    limit: Option<usize>,
+}
+
+impl SessionForm {
+    fn edit(session: &ReadingSession, limit: Option<usize>) -> Self {
+        let unit = session.unit;
+        Self {
+            mode: SessionMode::Edit,
+            state: FormState::with_values(vec![
+                format_hms(session.duration_secs as usize),
+                unit.format_position(session.start_page),
+                unit.format_position(session.end_page),
+            ]),
+            session: session.clone(),
+            limit,
+        }
+    }
+
+    /// Suggests splitting in the middle
+    fn split(session: &ReadingSession) -> Self {
+        let unit = session.unit;
+        let middle = session.start_page + (session.end_page.saturating_sub(session.start_page)) / 2;
+        Self {
+            mode: SessionMode::Split,
+            state: FormState::with_values(vec![
+                format_hms(session.duration_secs as usize / 2),
+                unit.format_position(middle),
+            ]),
+            session: session.clone(),
+            limit: None,
+        }
+    }
+
+    fn position(&self, field: SessionField) -> Result<usize, (SessionField, String)> {
+        let unit = self.session.unit;
+        match unit.parse_position(self.text(field)) {
+            Some(position) if self.limit.is_none_or(|limit| position <= limit) => Ok(position),
+            Some(_) => Err((
+                field,
+                format!("The book ends at {}", unit.format_position(self.limit.unwrap_or(0))),
+            )),
+            None => Err((field, format!("{} is required", field.label(unit)))),
+        }
+    }
+
+    fn parse_edit(&self) -> Result<SessionChange, (SessionField, String)> {
+        let duration_secs = parse_duration(self.text(SessionField::Duration))
+            .filter(|&secs| secs > 0)
+            .ok_or_else(|| (SessionField::Duration, "Duration must be above zero".to_string()))?;
+        // A longer session starts earlier, and that start has to be a real date
+        let start = i64::try_from(duration_secs)
+            .ok()
+            .and_then(TimeDelta::try_seconds)
+            .and_then(|duration| self.session.timestamp.checked_sub_signed(duration));
+        if start.is_none() {
+            return Err((SessionField::Duration, "Duration is too long".to_string()));
+        }
+        let start_page = self.position(SessionField::StartPage)?;
+        let end_page = self.position(SessionField::EndPage)?;
+        if end_page < start_page {
+            return Err((SessionField::EndPage, "The session cannot end before it starts".to_string()));
+        }
+        Ok(SessionChange::Edit {
+            start_page,
+            end_page,
+            duration_secs,
+        })
+    }
+
+    fn parse_split(&self) -> Result<SessionChange, (SessionField, String)> {
+        let first_secs = parse_duration(self.text(SessionField::FirstPart))
+            .filter(|&secs| secs > 0 && secs < self.session.duration_secs)
+            .ok_or_else(|| {
+                (
+                    SessionField::FirstPart,
+                    format!(
+                        "The first part must be shorter than {}",
+                        format_hms(self.session.duration_secs as usize)
+                    ),
+                )
+            })?;
+        let at_page = self.position(SessionField::SplitAt)?;
+        if at_page < self.session.start_page || at_page > self.session.end_page {
+            return Err((
+                SessionField::SplitAt,
+                format!(
+                    "Split between {} and {}",
+                    self.session.unit.format_position(self.session.start_page),
+                    self.session.unit.format_position(self.session.end_page)
+                ),
+            ));
+        }
+        Ok(SessionChange::Split { at_page, first_secs })
+    }
+}
+
+impl FieldForm for SessionForm {
+    type Field = SessionField;
+    type Output = SessionChange;
+
+    fn fields(&self) -> &[SessionField] {
+        match self.mode {
+            SessionMode::Edit => &EDIT_FIELDS,
+            SessionMode::Split => &SPLIT_FIELDS,
+        }
+    }
+
+    fn state(&self) -> &FormState {
+        &self.state
+    }
+
+    fn state_mut(&mut self) -> &mut FormState {
+        &mut self.state
+    }
+
+    fn accept_char(&mut self, field: SessionField, c: char) -> bool {
+        match field {
+            SessionField::Duration | SessionField::FirstPart => c.is_ascii_digit() || c == ':',
+            _ => c.is_ascii_digit() || (c == ':' && self.session.unit == ProgressUnit::Audio),
+        }
+    }
+
+    fn parse(&self) -> Result<SessionChange, (SessionField, String)> {
+        match self.mode {
+            SessionMode::Edit => self.parse_edit(),
+            SessionMode::Split => self.parse_split(),
+        }
+    }
+}
+
+pub enum HistoryInput {
+    Browse,
+    Editing(SessionForm),
+    Splitting(SessionForm),
+    ConfirmDelete,
+}
+
+/// Every recorded session of one book, oldest read first
+pub struct SessionHistory {
+    pub book_id: BookId,
+    pub selected_index: usize,
+    pub input: HistoryInput,
+}
+
+impl SessionHistory {
+    pub fn new(book: &BookProgress) -> Self {
+        // Start on the most recent session
+        Self {
+            book_id: book.id,
+            selected_index: book.all_sessions().count().saturating_sub(1),
+            input: HistoryInput::Browse,
+        }
+    }
+
+    fn session_count(&self, library: &Library) -> usize {
+        library.book(self.book_id).map_or(0, |b| b.all_sessions().count())
+    }
+
+    pub fn select_next(&mut self, library: &Library) {
+        let count = self.session_count(library);
+        if count > 0 {
+            self.selected_index = (self.selected_index + 1).min(count - 1);
+        }
+    }
+
+    pub fn select_prev(&mut self) {
+        self.selected_index = self.selected_index.saturating_sub(1);
+    }
+
+    pub fn selected_session<'a>(&self, library: &'a Library) -> Option<&'a ReadingSession> {
+        library.book(self.book_id)?.all_sessions().nth(self.selected_index)
+    }
+
+    /// Keep the selection inside the list, e.g. after a session was deleted
+    pub fn clamp_selection(&mut self, library: &Library) {
+        let count = self.session_count(library);
+        self.selected_index = self.selected_index.min(count.saturating_sub(1));
+    }
+
+    pub fn start_edit(&mut self, library: &Library) {
+        let Some(book) = library.book(self.book_id) else {
+            return;
+        };
+        if let Some(session) = self.selected_session(library) {
+            let limit = (session.unit == book.unit).then_some(book.total_pages);
+            self.input = HistoryInput::Editing(SessionForm::edit(session, limit));
+        }
+    }
+
+    pub fn start_split(&mut self, library: &Library) {
+        if let Some(session) = self.selected_session(library)
+            && session.duration_secs > 1
+        {
+            self.input = HistoryInput::Splitting(SessionForm::split(session));
+        }
+    }
+
+    pub fn start_delete(&mut self, library: &Library) {
+        if self.selected_session(library).is_some() {
+            self.input = HistoryInput::ConfirmDelete;
+        }
+    }
+
+    pub fn selected_id(&self, library: &Library) -> Option<Uuid> {
+        self.selected_session(library).map(|s| s.id)
+    }
+}
+
+/// Reading speed over a session: pages (or other units) per hour, or playback speed for audio
+fn pace(session: &ReadingSession) -> String {
+    if session.duration_secs == 0 {
+        return String::new();
+    }
+    let read = session.end_page.saturating_sub(session.start_page) as f64;
+    let hours = session.duration_secs as f64 / 3600.0;
+    match session.unit {
+        ProgressUnit::Audio => format!("{:.2}x", read / session.duration_secs as f64),
+        ProgressUnit::Pages => format!("{:.0} pages/h", read / hours),
+        ProgressUnit::Percent => format!("{:.1}%/h", read / hours),
+        ProgressUnit::Locations => format!("{:.0} locations/h", read / hours),
+    }
+}
+
+/// Start and end on the wall clock, marking sessions that ran past midnight
+fn time_span(session: &ReadingSession) -> String {
+    let start = session.started_at;
+    let end = session.timestamp;
+    let days = (end.date_naive() - start.date_naive()).num_days();
+    let span = format!("{}–{}", start.format("%Y-%m-%d %H:%M"), end.format("%H:%M"));
+    if days > 0 { format!("{} +{}d", span, days) } else { span }
+}
+
+fn session_line(session: &ReadingSession) -> String {
+    let unit = session.unit;
+    let mut line = format!(
+        "{:<25}  {:>8}  {} → {}  {:>14}  {}",
+        time_span(session),
+        format_hms(session.duration_secs as usize),
+        unit.format_position(session.start_page),
+        unit.format_position(session.end_page),
+        format!("(+{})", unit.format_amount(session.end_page.saturating_sub(session.start_page))),
+        pace(session)
+    );
+    match session.focus_blocks {
+        0 => {}
+        1 => line.push_str("  1 focus block"),
+        blocks => line.push_str(&format!("  {} focus blocks", blocks)),
+    }
+    line
+}
+
+pub fn draw_history(f: &mut Frame, library: &Library, history: &SessionHistory) {
+    let form = match &history.input {
+        HistoryInput::Editing(form) | HistoryInput::Splitting(form) => Some(form),
+        _ => None,
+    };
+    let form_height = form.map_or(0, |form| form.fields().len() as u16 + 3);
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .margin(2)
+        .constraints([
+            Constraint::Length(3),           // Title
+            Constraint::Min(5),              // Sessions
+            Constraint::Length(form_height), // Edit or split form
+            Constraint::Length(3),           // Controls
+        ])
+        .split(f.area());
+
+    let book = library.book(history.book_id);
+    let title = Paragraph::new(format!(
+        "Sessions: {}",
+        book.map_or("", |b| b.book_title.as_str())
+    ))
+    .style(Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD))
+    .alignment(Alignment::Center)
+    .block(Block::default().borders(Borders::ALL));
+    f.render_widget(title, chunks[0]);
+
+    // Sessions grouped by read, with a heading per read once the book has been re-read
+    let mut items: Vec<ListItem> = Vec::new();
+    let mut selected_item = None;
+    let mut count = 0;
+    if let Some(book) = book {
+        let runs = book
+            .previous_runs
+            .iter()
+            .map(|run| &run.sessions)
+            .chain(std::iter::once(&book.sessions));
+        for (run, sessions) in runs.enumerate() {
+            if !book.previous_runs.is_empty() {
+                items.push(
+                    ListItem::new(format!("Read #{}", run + 1))
+                        .style(Style::default().fg(Color::Cyan).add_modifier(Modifier::UNDERLINED)),
+                );
+            }
+            for session in sessions {
+                if count == history.selected_index {
+                    selected_item = Some(items.len());
+                }
+                items.push(ListItem::new(session_line(session)));
+                count += 1;
+            }
+        }
+    }
+    if count == 0 {
+        items = vec![ListItem::new("No sessions yet").style(Style::default().fg(Color::Gray))];
+    }
+    let list = List::new(items)
+        .block(Block::default().borders(Borders::ALL).title(format!("Sessions ({})", count)))
+        .highlight_style(Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD));
+    let mut state = ListState::default().with_selected(selected_item);
+    f.render_stateful_widget(list, chunks[1], &mut state);
+
+    if let Some(form) = form {
+        let unit = form.session.unit;
+        let mut lines: Vec<Line> = form
+            .fields()
+            .iter()
+            .enumerate()
+            .map(|(i, field)| {
+                let color = if i == form.focus() { Color::Yellow } else { Color::White };
+                Line::from(Span::styled(
+                    format!("{}: {}", field.label(unit), form.text_at(i)),
+                    Style::default().fg(color),
+                ))
+            })
+            .collect();
+        if let Some(error) = form.error() {
+            lines.push(Line::from(Span::styled(error.to_string(), Style::default().fg(Color::Red))));
+        }
+        let form_title = match history.input {
+            HistoryInput::Splitting(_) => "Split Session",
+            _ => "Edit Session",
+        };
+        let form_widget = Paragraph::new(lines).block(Block::default().borders(Borders::ALL).title(form_title));
+        f.render_widget(form_widget, chunks[2]);
+    }
+
+    let controls = match &history.input {
+        HistoryInput::Browse => "↑/↓: Select | E: Edit | S: Split | D: Delete | Esc: Back".to_string(),
+        HistoryInput::Editing(form) | HistoryInput::Splitting(form) => format!(
+            "{} | {} | Tab/Shift+Tab: Move | Esc: Cancel",
+            form.field().hint(),
+            if form.on_last_field() { "Enter: Save" } else { "Enter: Next" }
+        ),
+        HistoryInput::ConfirmDelete => "Delete this session? Y: Delete | N/Esc: Keep".to_string(),
+    };
+    let controls_widget = Paragraph::new(controls)
+        .style(Style::default().fg(Color::Cyan))
+        .alignment(Alignment::Center)
+        .block(Block::default().borders(Borders::ALL).title("Controls"));
+    f.render_widget(controls_widget, chunks[3]);
+}
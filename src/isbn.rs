@@ -0,0 +1,50 @@
+/// Strip spaces and hyphens and check an ISBN-10 or ISBN-13 checksum
+///
+/// Returns the bare digits (with a trailing `X` allowed for ISBN-10).
+pub fn normalize_isbn(input: &str) -> Result<String, String> {
+    let isbn: String = input
+        .chars()
+        .filter(|c| !c.is_whitespace() && *c != '-')
+        .map(|c| c.to_ascii_uppercase())
+        .collect();
+
+    let valid = match isbn.len() {
+        10 => isbn10_valid(&isbn),
+        13 => isbn13_valid(&isbn),
+        _ => return Err("ISBN must have 10 or 13 digits".to_string()),
+    };
+
+    if valid {
+        Ok(isbn)
+    } else {
+        Err(format!("{} is not a valid ISBN (checksum mismatch)", input.trim()))
+    }
+}
+
+/// Weights 10 down to 1, sum divisible by 11; the last digit may be X (10)
+fn isbn10_valid(isbn: &str) -> bool {
+    let mut sum = 0;
+    for (i, c) in isbn.chars().enumerate() {
+        let digit = match c {
+            'X' if i == 9 => 10,
+            _ => match c.to_digit(10) {
+                Some(d) => d,
+                None => return false,
+            },
+        };
+        sum += digit * (10 - i as u32);
+    }
+    sum % 11 == 0
+}
+
+/// Alternating weights 1 and 3, sum divisible by 10
+fn isbn13_valid(isbn: &str) -> bool {
+    let mut sum = 0;
+    for (i, c) in isbn.chars().enumerate() {
+        let Some(digit) = c.to_digit(10) else {
+            return false;
+        };
+        sum += if i % 2 == 0 { digit } else { digit * 3 };
+    }
+    sum % 10 == 0
+}
@@ -0,0 +1,144 @@
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+pub const LOCK_FILE: &str = "book_garden.lock";
+
+/// How long a new lock file may stay empty while its owner writes it
+const WRITE_GRACE: Duration = Duration::from_secs(5);
+
+/// Who holds the library lock
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct LockOwner {
+    pub pid: u32,
+    pub hostname: String,
+    pub since: DateTime<Local>,
+}
+
+impl LockOwner {
+    fn current() -> Self {
+        Self {
+            pid: std::process::id(),
+            hostname: hostname(),
+            since: Local::now(),
+        }
+    }
+
+    /// Stands in for the owner of a lock file that could not be read
+    fn unknown(path: &Path) -> Self {
+        let since = fs::metadata(path).and_then(|m| m.modified()).map_or_else(|_| Local::now(), DateTime::from);
+        Self {
+            pid: 0,
+            hostname: String::new(),
+            since,
+        }
+    }
+
+    /// A lock left behind by a process on this machine that no longer exists
+    fn is_stale(&self) -> bool {
+        self.pid != 0 && self.hostname == hostname() && !process_alive(self.pid)
+    }
+
+    /// The owning process for messages, e.g. "process 4242 on laptop"
+    pub fn describe(&self) -> String {
+        if self.pid == 0 {
+            "an unknown process (the lock file could not be read)".to_string()
+        } else {
+            format!("process {} on {}", self.pid, self.hostname)
+        }
+    }
+}
+
+/// Advisory lock on a data directory, released when dropped
+pub struct LibraryLock {
+    path: PathBuf,
+}
+
+pub enum LockStatus {
+    Acquired(LibraryLock),
+    /// Another running instance has the library open
+    Held(LockOwner),
+}
+
+/// Try to take the lock for `dir`, clearing it first if its owner has exited
+pub fn acquire_lock(dir: &Path) -> std::io::Result<LockStatus> {
+    fs::create_dir_all(dir)?;
+    let path = dir.join(LOCK_FILE);
+
+    // Two attempts: the second follows removing a stale lock
+    for _ in 0..2 {
+        match OpenOptions::new().write(true).create_new(true).open(&path) {
+            Ok(mut file) => {
+                file.write_all(serde_json::to_string(&LockOwner::current())?.as_bytes())?;
+                file.sync_all()?;
+                return Ok(LockStatus::Acquired(LibraryLock { path }));
+            }
+            Err(e) if e.kind() == io::ErrorKind::AlreadyExists => match read_owner(&path) {
+                Some(owner) if !owner.is_stale() => return Ok(LockStatus::Held(owner)),
+                Some(_) => fs::remove_file(&path)?,
+                // Empty long after it was created: the owner died before writing it
+                None if abandoned(&path) => fs::remove_file(&path)?,
+                // Possibly still being written by an instance starting right now
+                None => return Ok(LockStatus::Held(LockOwner::unknown(&path))),
+            },
+            Err(e) => return Err(e),
+        }
+    }
+
+    Err(io::Error::other(format!("could not acquire {}", path.display())))
+}
+
+fn abandoned(path: &Path) -> bool {
+    fs::metadata(path).is_ok_and(|meta| {
+        meta.len() == 0
+            && meta
+                .modified()
+                .is_ok_and(|modified| SystemTime::now().duration_since(modified).unwrap_or_default() >= WRITE_GRACE)
+    })
+}
+
+fn read_owner(path: &Path) -> Option<LockOwner> {
+    let data = fs::read_to_string(path).ok()?;
+    serde_json::from_str(&data).ok()
+}
+
+impl Drop for LibraryLock {
+    fn drop(&mut self) {
+        // Only remove the file if it is still ours
+        if read_owner(&self.path).is_some_and(|owner| owner.pid == std::process::id()) {
+            let _ = fs::remove_file(&self.path);
+        }
+    }
+}
+
+#[cfg(unix)]
+fn process_alive(pid: u32) -> bool {
+    // Signal 0 only checks whether the process exists; EPERM means it does
+    // but belongs to someone else.
+    let result = unsafe { libc::kill(pid as libc::pid_t, 0) };
+    result == 0 || io::Error::last_os_error().raw_os_error() == Some(libc::EPERM)
+}
+
+#[cfg(not(unix))]
+fn process_alive(_pid: u32) -> bool {
+    true
+}
+
+#[cfg(unix)]
+fn hostname() -> String {
+    let mut buf = [0u8; 256];
+    let result = unsafe { libc::gethostname(buf.as_mut_ptr() as *mut libc::c_char, buf.len()) };
+    if result != 0 {
+        return String::new();
+    }
+    let len = buf.iter().position(|&b| b == 0).unwrap_or(buf.len());
+    String::from_utf8_lossy(&buf[..len]).into_owned()
+}
+
+#[cfg(not(unix))]
+fn hostname() -> String {
+    std::env::var("COMPUTERNAME").unwrap_or_default()
+}
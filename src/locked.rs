@@ -0,0 +1,74 @@
+use ratatui::{
+    layout::{Alignment, Constraint, Direction, Layout},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Paragraph, Wrap},
+    Frame,
+};
+
+use crate::lock::LockOwner;
+
+pub fn draw_locked(f: &mut Frame, owner: &LockOwner, can_open_read_only: bool) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .margin(2)
+        .constraints([
+            Constraint::Length(3), // Title
+            Constraint::Min(8),    // Details
+            Constraint::Length(3), // Controls
+        ])
+        .split(f.area());
+
+    // Title
+    let title = Paragraph::new("Library Open Elsewhere")
+        .style(Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD))
+        .alignment(Alignment::Center)
+        .block(Block::default().borders(Borders::ALL));
+    f.render_widget(title, chunks[0]);
+
+    // Details
+    let details = vec![
+        Line::from(Span::styled(
+            "Another Book Garden is using this library.",
+            Style::default().fg(Color::White).add_modifier(Modifier::BOLD),
+        )),
+        Line::from(""),
+        Line::from(vec![
+            Span::styled("Held by: ", Style::default().fg(Color::Gray)),
+            Span::styled(
+                owner.describe(),
+                Style::default().fg(Color::Cyan),
+            ),
+        ]),
+        Line::from(vec![
+            Span::styled("Since: ", Style::default().fg(Color::Gray)),
+            Span::styled(
+                owner.since.format("%Y-%m-%d %H:%M").to_string(),
+                Style::default().fg(Color::Cyan),
+            ),
+        ]),
+        Line::from(""),
+        Line::from(Span::styled(
+            "Close it first, or open read-only: you can browse and time sessions, but nothing is saved.",
+            Style::default().fg(Color::Gray),
+        )),
+    ];
+
+    let details_widget = Paragraph::new(details)
+        .wrap(Wrap { trim: false })
+        .block(Block::default().borders(Borders::ALL).title("Details"));
+    f.render_widget(details_widget, chunks[1]);
+
+    // Controls
+    let controls = if can_open_read_only {
+        "R: Open Read-Only | Q: Quit"
+    } else {
+        "Q: Quit"
+    };
+
+    let controls_widget = Paragraph::new(controls)
+        .style(Style::default().fg(Color::Cyan))
+        .alignment(Alignment::Center)
+        .block(Block::default().borders(Borders::ALL).title("Controls"));
+    f.render_widget(controls_widget, chunks[2]);
+}
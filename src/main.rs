@@ -1,170 +1,858 @@
 mod app;
+mod cli;
+mod config;
+mod isbn;
 mod storage;
 mod ui;
 mod plant;
 mod book_select;
+mod chapters;
+mod checkpoint;
+mod history;
+mod notes;
+mod session_log;
+mod stats;
+mod lock;
+mod locked;
+mod recovery;
+mod resume;
+mod form;
 
 use std::{
+    env,
     io::stdout,
+    path::Path,
+    process,
     time::Duration,
 };
 
-use app::{AppState, TimerState};
+use app::{AppState, NextUp, RatingStage, TimerState};
 use book_select::{BookSelector, InputMode, draw_book_select};
+use form::FieldForm;
+use chapters::{draw_chapters, ChapterEditor, ChapterInput};
+use history::{draw_history, HistoryInput, SessionChange, SessionHistory};
+use notes::{draw_note_search, draw_notes, NoteSearch, NotesPanel};
+use session_log::{draw_session_log, SessionLog};
+use stats::draw_library_stats;
 use crossterm::{
     event::{self, Event, KeyCode, KeyEvent},
+    style::Print,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
     ExecutableCommand,
 };
 use ratatui::{backend::CrosstermBackend, Terminal};
-use storage::{load_library, add_book};
+use lock::{acquire_lock, LockOwner, LockStatus};
+use locked::draw_locked;
+use recovery::draw_recovery;
+use resume::draw_resume;
+use checkpoint::{load_checkpoint, ActiveSession, Checkpointer};
+use cli::{parse_args, Command, USAGE};
+use config::{load_config, resolve_data_dir};
+use storage::{backend_path, migrate_legacy_dir, migrate_storage, open_backend, peek_library, discard_journal, restore_backup, reset_library, truncate_journal, CorruptLibrary, Library, LibraryStatus, LibraryStore, ReadingStatus, SCHEMA_VERSION};
 
 enum AppMode {
+    Locked,
+    Recovery,
+    ResumeSession,
     BookSelection,
+    Chapters,
+    History,
+    LogSession,
+    Notes,
+    NoteSearch,
+    Stats,
     Reading,
 }
 
+/// Raw mode and the alternate screen, undone however the event loop is left, errors included
+struct TerminalGuard;
+
+impl TerminalGuard {
+    fn enter() -> std::io::Result<Self> {
+        enable_raw_mode()?;
+        // Created before the screen switch so a failure there still leaves raw mode
+        let guard = TerminalGuard;
+        stdout().execute(EnterAlternateScreen)?;
+        Ok(guard)
+    }
+}
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        let _ = disable_raw_mode();
+        let _ = stdout().execute(LeaveAlternateScreen);
+    }
+}
+
+/// The checkpoint of a session whose timer was still open when the last run ended, if its book is still there
+fn orphaned_session(data_dir: &Path, library: &Library) -> Option<ActiveSession> {
+    load_checkpoint(data_dir).filter(|active| library.book(active.book_id).is_some())
+}
+
+/// Wait briefly for a key press, returning its code if one arrived
+fn next_key() -> std::io::Result<Option<KeyCode>> {
+    if event::poll(Duration::from_millis(100))?
+        && let Event::Key(KeyEvent { code, .. }) = event::read()?
+    {
+        return Ok(Some(code));
+    }
+    Ok(None)
+}
+
 fn main() -> std::io::Result<()> {
-    enable_raw_mode()?;
-    stdout().execute(EnterAlternateScreen)?;
-    let backend = CrosstermBackend::new(stdout());
-    let mut terminal = Terminal::new(backend)?;
+    let args = match parse_args(env::args().skip(1)) {
+        Ok(args) => args,
+        Err(e) => {
+            eprintln!("book_garden: {}\n\n{}", e, USAGE);
+            process::exit(2);
+        }
+    };
+    if args.help {
+        println!("{}", USAGE);
+        return Ok(());
+    }
+
+    let data_dir = resolve_data_dir(args.data_dir);
+
+    // Held until main returns; dropping it removes the lock file
+    let (lock, lock_owner): (_, Option<LockOwner>) = match acquire_lock(&data_dir)? {
+        LockStatus::Acquired(lock) => (Some(lock), None),
+        LockStatus::Held(owner) => (None, Some(owner)),
+    };
+
+    // Moving files in is a write, so only the instance holding the lock does it
+    if lock_owner.is_none()
+        && let Some(legacy) = migrate_legacy_dir(&data_dir)?
+    {
+        println!("Moved library from {} to {}", legacy.display(), data_dir.display());
+    }
 
-    let mut library = load_library()?;
+    let config = load_config(&data_dir)?;
+
+    if let Some(Command::MigrateStorage { from, to }) = args.command {
+        if let Some(owner) = lock_owner {
+            eprintln!(
+                "book_garden: the library is open in {}; close it before migrating",
+                owner.describe()
+            );
+            process::exit(1);
+        }
+        match migrate_storage(&data_dir, &config, from, to) {
+            Ok(count) => {
+                println!(
+                    "Copied {} books from {} to {}.\nSet \"backend\": \"{}\" in {} to use it.",
+                    count,
+                    backend_path(from, &data_dir).display(),
+                    backend_path(to, &data_dir).display(),
+                    to,
+                    data_dir.join("config.json").display(),
+                );
+                return Ok(());
+            }
+            Err(e) => {
+                eprintln!("book_garden: migrate-storage failed: {}", e);
+                drop(lock);
+                process::exit(1);
+            }
+        }
+    }
+
+    // Without the lock the files are the owner's: read them for browsing, but
+    // never migrate, quarantine or compact them
+    let peeked = lock_owner.as_ref().map(|_| peek_library(config.backend, &data_dir));
+    let browsable = !matches!(peeked, Some(None));
+
+    let mut backend = open_backend(config.backend, &data_dir, &config);
+    let (library, mut app_mode, mut corrupt): (Library, AppMode, Option<CorruptLibrary>) = match peeked {
+        Some(library) => (library.unwrap_or_else(Library::new), AppMode::Locked, None),
+        None => match backend.load()? {
+            LibraryStatus::Loaded(library) => (library, AppMode::BookSelection, None),
+            LibraryStatus::Corrupt(details) => (
+                Library::new(),
+                AppMode::Recovery,
+                Some(details),
+            ),
+            LibraryStatus::TooNew(version) => {
+                eprintln!(
+                    "book_garden: {} was written by a newer version of Book Garden \
+                     (schema version {}, this build supports up to {}). \
+                     Please upgrade book_garden to open it; the file has not been modified.",
+                    backend_path(config.backend, &data_dir).display(),
+                    version,
+                    SCHEMA_VERSION,
+                );
+                drop(lock);
+                process::exit(1);
+            }
+        },
+    };
+    let mut store = LibraryStore::new(backend, library);
+    if lock_owner.is_some() {
+        store.set_read_only();
+    }
+
+    // A session whose timer was still open when the last run ended
+    let mut checkpointer = Checkpointer::new(&data_dir);
+    let mut orphan = None;
+    if lock_owner.is_none()
+        && corrupt.is_none()
+        && let Some(active) = orphaned_session(&data_dir, store.library())
+    {
+        orphan = Some(active);
+        app_mode = AppMode::ResumeSession;
+    }
     let mut selector = BookSelector::new();
-    let mut app_mode = AppMode::BookSelection;
     let mut app: Option<AppState> = None;
+    let mut chapter_editor: Option<ChapterEditor> = None;
+    let mut notes_panel: Option<NotesPanel> = None;
+    let mut session_log: Option<SessionLog> = None;
+    let mut session_history: Option<SessionHistory> = None;
+    let mut note_search = NoteSearch::new();
+
+    let terminal_guard = TerminalGuard::enter()?;
+    let backend = CrosstermBackend::new(stdout());
+    let mut terminal = Terminal::new(backend)?;
 
     loop {
+        // Focus blocks and breaks run out on any screen, not just while reading
+        if let Some(ref mut reading_app) = app
+            && reading_app.tick_focus()
+        {
+            stdout().execute(Print('\x07'))?;
+        }
+        // An instance without the lock leaves the owner's checkpoint alone; a
+        // failed write is retried on the next pass while the session goes on
+        if let Some(ref mut reading_app) = app
+            && lock_owner.is_none()
+        {
+            reading_app.checkpoint_error = checkpointer
+                .update(reading_app.active_session())
+                .err()
+                .map(|e| e.to_string());
+        }
+
         match app_mode {
-            AppMode::BookSelection => {
-                terminal.draw(|f| draw_book_select(f, &library, &selector))?;
-
-                if event::poll(Duration::from_millis(100))? {
-                    if let Event::Key(KeyEvent { code, .. }) = event::read()? {
-                        match selector.input_mode {
-                            InputMode::Selection => match code {
-                                KeyCode::Up => selector.select_prev(&library),
-                                KeyCode::Down => selector.select_next(&library),
-                                KeyCode::Enter => {
-                                    if !library.books.is_empty() && selector.selected_index < library.books.len() {
-                                        let book = &library.books[selector.selected_index];
-                                        app = Some(AppState::new(selector.selected_index, book));
-                                        app_mode = AppMode::Reading;
-                                    }
+            AppMode::Locked => {
+                if let Some(ref owner) = lock_owner {
+                    terminal.draw(|f| draw_locked(f, owner, browsable))?;
+
+                    if let Some(code) = next_key()? {
+                        match code {
+                            KeyCode::Char('r') | KeyCode::Char('R') if browsable => {
+                                selector.read_only = true;
+                                app_mode = AppMode::BookSelection;
+                            }
+                            KeyCode::Char('q') | KeyCode::Char('Q') => break,
+                            _ => {}
+                        }
+                    }
+                }
+            }
+            AppMode::ResumeSession => {
+                let Some(active) = orphan.clone() else {
+                    app_mode = AppMode::BookSelection;
+                    continue;
+                };
+                let Some(book) = store.library().book(active.book_id) else {
+                    app_mode = AppMode::BookSelection;
+                    continue;
+                };
+                terminal.draw(|f| draw_resume(f, &active, book))?;
+
+                if let Some(code) = next_key()? {
+                    match code {
+                        KeyCode::Char('r') | KeyCode::Char('R') => {
+                            let mut reading_app = AppState::new(book);
+                            reading_app.default_idle_minutes = config.idle_minutes;
+                            reading_app.focus_minutes = config.focus_minutes;
+                            reading_app.break_minutes = config.break_minutes;
+                            reading_app.resume_session(&active);
+                            selector.select_book(store.library(), active.book_id);
+                            app = Some(reading_app);
+                            orphan = None;
+                            app_mode = AppMode::Reading;
+                        }
+                        KeyCode::Char('s') | KeyCode::Char('S') => {
+                            let session = active.to_session(book);
+                            store.log_session(active.book_id, session)?;
+                            checkpointer.clear()?;
+                            selector.select_book(store.library(), active.book_id);
+                            orphan = None;
+                            app_mode = AppMode::BookSelection;
+                        }
+                        KeyCode::Char('d') | KeyCode::Char('D') => {
+                            checkpointer.clear()?;
+                            orphan = None;
+                            app_mode = AppMode::BookSelection;
+                        }
+                        // The checkpoint stays, so the next launch asks again
+                        KeyCode::Char('q') | KeyCode::Char('Q') | KeyCode::Esc => break,
+                        _ => {}
+                    }
+                }
+            }
+            AppMode::Recovery => {
+                if let Some(ref details) = corrupt {
+                    terminal.draw(|f| draw_recovery(f, details))?;
+
+                    if let Some(code) = next_key()? {
+                        match code {
+                            // A bad journal leaves the snapshot intact, so it is never replaced
+                            KeyCode::Char('k') | KeyCode::Char('K') if details.in_journal && details.line > 1 => {
+                                truncate_journal(&data_dir, details.line)?;
+                                store.reload()?;
+                                corrupt = None;
+                                app_mode = AppMode::BookSelection;
+                            }
+                            KeyCode::Char('d') | KeyCode::Char('D') if details.in_journal => {
+                                discard_journal(&data_dir)?;
+                                store.reload()?;
+                                corrupt = None;
+                                app_mode = AppMode::BookSelection;
+                            }
+                            KeyCode::Char('r') | KeyCode::Char('R') if !details.in_journal => {
+                                if let Some(ref backup) = details.backup {
+                                    restore_backup(&data_dir, backup)?;
+                                    store.reload()?;
+                                    corrupt = None;
+                                    app_mode = AppMode::BookSelection;
                                 }
-                                KeyCode::Char('n') | KeyCode::Char('N') => {
-                                    selector.input_mode = InputMode::EnteringTitle;
-                                    selector.new_book_title.clear();
-                                    selector.new_book_pages.clear();
+                            }
+                            KeyCode::Char('e') | KeyCode::Char('E') if !details.in_journal => {
+                                reset_library(&data_dir)?;
+                                store.reload()?;
+                                corrupt = None;
+                                app_mode = AppMode::BookSelection;
+                            }
+                            KeyCode::Char('q') | KeyCode::Char('Q') => break,
+                            _ => {}
+                        }
+                    }
+                }
+                // The repaired library may still hold the book of an unfinished session
+                if corrupt.is_none()
+                    && let Some(active) = orphaned_session(&data_dir, store.library())
+                {
+                    orphan = Some(active);
+                    app_mode = AppMode::ResumeSession;
+                }
+            }
+            AppMode::Chapters => {
+                if let Some(ref mut editor) = chapter_editor {
+                    terminal.draw(|f| draw_chapters(f, editor))?;
+
+                    if let Some(code) = next_key()? {
+                        let changed = match editor.input_mode {
+                            ChapterInput::Browse => match code {
+                                KeyCode::Up => {
+                                    editor.select_prev();
+                                    false
                                 }
-                                KeyCode::Char('q') | KeyCode::Char('Q') => break,
-                                _ => {}
+                                KeyCode::Down => {
+                                    editor.select_next();
+                                    false
+                                }
+                                KeyCode::Char('a') | KeyCode::Char('A') => {
+                                    editor.start_input(ChapterInput::Adding);
+                                    false
+                                }
+                                KeyCode::Char('i') | KeyCode::Char('I') => {
+                                    editor.start_input(ChapterInput::Importing);
+                                    false
+                                }
+                                KeyCode::Char('d') | KeyCode::Char('D') => {
+                                    editor.delete_selected();
+                                    true
+                                }
+                                KeyCode::Esc | KeyCode::Char('b') | KeyCode::Char('B') => {
+                                    app_mode = AppMode::BookSelection;
+                                    false
+                                }
+                                _ => false,
                             },
-                            InputMode::EnteringTitle => match code {
-                                KeyCode::Enter => {
-                                    if !selector.new_book_title.is_empty() {
-                                        selector.input_mode = InputMode::EnteringPages;
-                                    }
+                            ChapterInput::Adding | ChapterInput::Importing => match code {
+                                KeyCode::Enter if editor.input_mode == ChapterInput::Adding => {
+                                    editor.submit_chapter()
                                 }
+                                KeyCode::Enter => editor.submit_import(),
                                 KeyCode::Backspace => {
-                                    selector.new_book_title.pop();
+                                    editor.input.pop();
+                                    false
                                 }
                                 KeyCode::Esc => {
-                                    selector.input_mode = InputMode::Selection;
+                                    editor.input_mode = ChapterInput::Browse;
+                                    editor.error = None;
+                                    false
                                 }
                                 KeyCode::Char(c) => {
-                                    selector.new_book_title.push(c);
+                                    editor.input.push(c);
+                                    false
+                                }
+                                _ => false,
+                            },
+                        };
+
+                        if changed {
+                            store.set_chapters(editor.book_id, editor.chapters.clone())?;
+                        }
+                    }
+                }
+            }
+            AppMode::History => {
+                if let Some(ref mut history) = session_history {
+                    terminal.draw(|f| draw_history(f, store.library(), history))?;
+
+                    if let Some(code) = next_key()? {
+                        let book_id = history.book_id;
+                        match history.input {
+                            HistoryInput::Browse => match code {
+                                KeyCode::Up => history.select_prev(),
+                                KeyCode::Down => history.select_next(store.library()),
+                                KeyCode::Char('e') | KeyCode::Char('E') => history.start_edit(store.library()),
+                                KeyCode::Char('s') | KeyCode::Char('S') => history.start_split(store.library()),
+                                KeyCode::Char('d') | KeyCode::Char('D') => history.start_delete(store.library()),
+                                KeyCode::Esc | KeyCode::Char('b') | KeyCode::Char('B') => {
+                                    selector.select_book(store.library(), book_id);
+                                    app_mode = AppMode::BookSelection;
                                 }
                                 _ => {}
                             },
-                            InputMode::EnteringPages => match code {
-                                KeyCode::Enter => {
-                                    if let Ok(pages) = selector.new_book_pages.parse::<usize>() {
-                                        if pages > 0 {
-                                            let book_index = add_book(selector.new_book_title.clone(), pages)?;
-                                            library = load_library()?;
-                                            selector.selected_index = book_index;
-                                            selector.input_mode = InputMode::Selection;
-                                        }
+                            HistoryInput::ConfirmDelete => match code {
+                                KeyCode::Char('y') | KeyCode::Char('Y') => {
+                                    if let Some(session_id) = history.selected_id(store.library()) {
+                                        store.delete_session(book_id, session_id)?;
                                     }
+                                    history.clamp_selection(store.library());
+                                    history.input = HistoryInput::Browse;
                                 }
-                                KeyCode::Backspace => {
-                                    selector.new_book_pages.pop();
+                                KeyCode::Char('n') | KeyCode::Char('N') | KeyCode::Esc => {
+                                    history.input = HistoryInput::Browse;
                                 }
-                                KeyCode::Esc => {
-                                    selector.input_mode = InputMode::Selection;
-                                }
-                                KeyCode::Char(c) if c.is_ascii_digit() => {
-                                    selector.new_book_pages.push(c);
+                                _ => {}
+                            },
+                            HistoryInput::Editing(ref mut form) | HistoryInput::Splitting(ref mut form) => match code {
+                                KeyCode::Enter if form.on_last_field() => {
+                                    if let Some(change) = form.submit()
+                                        && let Some(session_id) = history.selected_id(store.library())
+                                    {
+                                        match change {
+                                            SessionChange::Edit { start_page, end_page, duration_secs } => {
+                                                store.edit_session(book_id, session_id, start_page, end_page, duration_secs)?;
+                                            }
+                                            SessionChange::Split { at_page, first_secs } => {
+                                                store.split_session(book_id, session_id, at_page, first_secs)?;
+                                            }
+                                        }
+                                        history.input = HistoryInput::Browse;
+                                    }
                                 }
+                                KeyCode::Enter | KeyCode::Tab | KeyCode::Down => form.next_field(),
+                                KeyCode::BackTab | KeyCode::Up => form.prev_field(),
+                                KeyCode::Backspace => form.pop_char(),
+                                KeyCode::Esc => history.input = HistoryInput::Browse,
+                                KeyCode::Char(c) => form.push_char(c),
                                 _ => {}
                             },
                         }
                     }
                 }
             }
+            AppMode::LogSession => {
+                if let Some(ref mut log) = session_log {
+                    terminal.draw(|f| draw_session_log(f, log))?;
+
+                    if let Some(code) = next_key()? {
+                        match code {
+                            KeyCode::Enter if log.on_last_field() => {
+                                if let Some(session) = log.submit() {
+                                    let book_id = log.book_id;
+                                    store.log_session(book_id, session)?;
+                                    selector.select_book(store.library(), book_id);
+                                    app_mode = AppMode::BookSelection;
+                                }
+                            }
+                            KeyCode::Enter | KeyCode::Tab | KeyCode::Down => log.next_field(),
+                            KeyCode::BackTab | KeyCode::Up => log.prev_field(),
+                            KeyCode::Backspace => log.pop_char(),
+                            KeyCode::Esc => app_mode = AppMode::BookSelection,
+                            KeyCode::Char(c) => log.push_char(c),
+                            _ => {}
+                        }
+                    }
+                }
+            }
+            AppMode::Notes => {
+                if let Some(ref mut panel) = notes_panel {
+                    terminal.draw(|f| draw_notes(f, store.library(), panel))?;
+
+                    if let Some(code) = next_key()? {
+                        match code {
+                            KeyCode::Up => panel.select_prev(),
+                            KeyCode::Down => panel.select_next(store.library()),
+                            KeyCode::Char('d') | KeyCode::Char('D') => {
+                                if let Some(note) = panel.selected_note(store.library()) {
+                                    let note_id = note.id;
+                                    store.delete_note(panel.book_id, note_id)?;
+                                    panel.clamp_selection(store.library());
+                                    if let Some(ref mut reading_app) = app
+                                        && let Some(book) = store.library().book(reading_app.book_id)
+                                    {
+                                        reading_app.note_count = book.notes.len();
+                                    }
+                                }
+                            }
+                            KeyCode::Esc | KeyCode::Char('b') | KeyCode::Char('B') => {
+                                app_mode = if panel.from_search {
+                                    AppMode::NoteSearch
+                                } else {
+                                    AppMode::Reading
+                                };
+                            }
+                            _ => {}
+                        }
+                    }
+                }
+            }
+            AppMode::NoteSearch => {
+                terminal.draw(|f| draw_note_search(f, store.library(), &note_search))?;
+
+                if let Some(code) = next_key()? {
+                    match code {
+                        KeyCode::Up => note_search.select_prev(),
+                        KeyCode::Down => note_search.select_next(store.library()),
+                        KeyCode::Enter => {
+                            if let Some((book, index)) = note_search.selected(store.library()) {
+                                let mut panel = NotesPanel::new(book.id, true);
+                                panel.selected_index = index;
+                                notes_panel = Some(panel);
+                                app_mode = AppMode::Notes;
+                            }
+                        }
+                        KeyCode::Backspace => {
+                            note_search.query.pop();
+                            note_search.selected_index = 0;
+                        }
+                        KeyCode::Esc => app_mode = AppMode::BookSelection,
+                        KeyCode::Char(c) => {
+                            note_search.query.push(c);
+                            note_search.selected_index = 0;
+                        }
+                        _ => {}
+                    }
+                }
+            }
+            AppMode::Stats => {
+                terminal.draw(|f| draw_library_stats(f, store.library()))?;
+
+                if let Some(KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('Q')) = next_key()? {
+                    app_mode = AppMode::BookSelection;
+                }
+            }
+            AppMode::BookSelection => {
+                terminal.draw(|f| draw_book_select(f, store.library(), &selector))?;
+
+                if let Some(code) = next_key()? {
+                    match selector.input_mode {
+                        InputMode::Selection => match code {
+                            KeyCode::Up => selector.select_prev(store.library()),
+                            KeyCode::Down => selector.select_next(store.library()),
+                            KeyCode::Enter => {
+                                if let Some(book) = selector.selected_book(store.library()) {
+                                    match app {
+                                        Some(ref mut reading_app) => reading_app.load_book(book),
+                                        None => {
+                                            let mut reading_app = AppState::new(book);
+                                            reading_app.read_only = store.is_read_only();
+                                            reading_app.default_idle_minutes = config.idle_minutes;
+                                            reading_app.focus_minutes = config.focus_minutes;
+                                            reading_app.break_minutes = config.break_minutes;
+                                            app = Some(reading_app);
+                                        }
+                                    }
+                                    app_mode = AppMode::Reading;
+                                }
+                            }
+                            KeyCode::Char('n') | KeyCode::Char('N') => selector.start_add(),
+                            KeyCode::Char('e') | KeyCode::Char('E') => {
+                                if let Some(book) = selector.selected_book(store.library()) {
+                                    let book = book.clone();
+                                    selector.start_edit(&book);
+                                }
+                            }
+                            KeyCode::Char('d') | KeyCode::Char('D') => {
+                                if let Some(book) = selector.selected_book(store.library()) {
+                                    selector.input_mode = InputMode::ConfirmDelete(book.id);
+                                }
+                            }
+                            KeyCode::Char('a') | KeyCode::Char('A') => {
+                                if let Some(book) = selector.selected_book(store.library()) {
+                                    if matches!(book.status, ReadingStatus::Abandoned { .. }) {
+                                        let book_id = book.id;
+                                        store.set_abandoned(book_id, None)?;
+                                        selector.select_book(store.library(), book_id);
+                                    } else {
+                                        selector.start_abandon(book.id);
+                                    }
+                                }
+                            }
+                            KeyCode::Char('r') | KeyCode::Char('R') => {
+                                if let Some(book) = selector.selected_book(store.library())
+                                    && matches!(book.status, ReadingStatus::Finished | ReadingStatus::Abandoned { .. })
+                                {
+                                    let book_id = book.id;
+                                    store.start_rerun(book_id)?;
+                                    selector.select_book(store.library(), book_id);
+                                }
+                            }
+                            KeyCode::Char('c') | KeyCode::Char('C') => {
+                                if let Some(book) = selector.selected_book(store.library()) {
+                                    chapter_editor = Some(ChapterEditor::new(book));
+                                    app_mode = AppMode::Chapters;
+                                }
+                            }
+                            KeyCode::Char('h') | KeyCode::Char('H') => {
+                                if let Some(book) = selector.selected_book(store.library()) {
+                                    session_history = Some(SessionHistory::new(book));
+                                    app_mode = AppMode::History;
+                                }
+                            }
+                            KeyCode::Char('l') | KeyCode::Char('L') => {
+                                if let Some(book) = selector.selected_book(store.library()) {
+                                    session_log = Some(SessionLog::new(book));
+                                    app_mode = AppMode::LogSession;
+                                }
+                            }
+                            KeyCode::Char('/') => {
+                                note_search = NoteSearch::new();
+                                app_mode = AppMode::NoteSearch;
+                            }
+                            KeyCode::Char('s') | KeyCode::Char('S') => selector.toggle_sort(store.library()),
+                            KeyCode::Char('t') | KeyCode::Char('T') => app_mode = AppMode::Stats,
+                            KeyCode::Tab => selector.cycle_filter(store.library()),
+                            KeyCode::Char('q') | KeyCode::Char('Q') => break,
+                            _ => {}
+                        },
+                        InputMode::AbandonReason(book_id) => match code {
+                            KeyCode::Enter => {
+                                let reason = selector.abandon_reason.trim().to_string();
+                                store.set_abandoned(book_id, Some(reason))?;
+                                selector.select_book(store.library(), book_id);
+                                selector.input_mode = InputMode::Selection;
+                            }
+                            KeyCode::Backspace => {
+                                selector.abandon_reason.pop();
+                            }
+                            KeyCode::Esc => {
+                                selector.input_mode = InputMode::Selection;
+                            }
+                            KeyCode::Char(c) => {
+                                selector.abandon_reason.push(c);
+                            }
+                            _ => {}
+                        },
+                        InputMode::AddingBook | InputMode::EditingBook(_) => match code {
+                            KeyCode::Enter if selector.form.on_last_field() => {
+                                if let Some(details) = selector.form.submit() {
+                                    let book_id = match selector.input_mode {
+                                        InputMode::EditingBook(book_id) => {
+                                            store.edit_book(book_id, details)?;
+                                            book_id
+                                        }
+                                        _ => store.add_book(details)?,
+                                    };
+                                    selector.select_book(store.library(), book_id);
+                                    selector.input_mode = InputMode::Selection;
+                                }
+                            }
+                            KeyCode::Enter | KeyCode::Tab | KeyCode::Down => {
+                                selector.form.next_field();
+                            }
+                            KeyCode::BackTab | KeyCode::Up => {
+                                selector.form.prev_field();
+                            }
+                            KeyCode::Backspace => {
+                                selector.form.pop_char();
+                            }
+                            KeyCode::Esc => {
+                                selector.input_mode = InputMode::Selection;
+                            }
+                            KeyCode::Char(c) => {
+                                selector.form.push_char(c);
+                            }
+                            _ => {}
+                        },
+                        InputMode::ConfirmDelete(book_id) => match code {
+                            KeyCode::Char('y') | KeyCode::Char('Y') => {
+                                store.delete_book(book_id)?;
+                                selector.clamp_selection(store.library());
+                                selector.input_mode = InputMode::Selection;
+                            }
+                            KeyCode::Char('n') | KeyCode::Char('N') | KeyCode::Esc => {
+                                selector.input_mode = InputMode::Selection;
+                            }
+                            _ => {}
+                        },
+                    }
+                }
+            }
             AppMode::Reading => {
                 if let Some(ref mut reading_app) = app {
+                    reading_app.check_idle();
                     terminal.draw(|f| ui::draw_ui(f, reading_app))?;
 
-                    if event::poll(Duration::from_millis(100))? {
-                        if let Event::Key(KeyEvent { code, .. }) = event::read()? {
-                            match code {
-                                KeyCode::Char(' ') => {
-                                    if reading_app.timer_state == TimerState::Running {
-                                        reading_app.pause();
-                                    } else {
-                                        reading_app.start();
+                    if let Some(code) = next_key()? {
+                        reading_app.touch();
+                        match code {
+                            // After a long time without keys, first ask whether reading went on
+                            code if reading_app.idle_prompt.is_some() => match code {
+                                KeyCode::Char('y') | KeyCode::Char('Y') | KeyCode::Enter => {
+                                    reading_app.idle_prompt = None;
+                                }
+                                KeyCode::Char('t') | KeyCode::Char('T') => reading_app.trim_idle(),
+                                KeyCode::Char('p') | KeyCode::Char('P') | KeyCode::Esc => {
+                                    reading_app.pause_at_last_activity();
+                                }
+                                _ => {}
+                            },
+                            // Typing a note takes every key until Enter or Esc
+                            code if reading_app.note_draft.is_some() => match code {
+                                KeyCode::Enter => reading_app.save_note(&mut store),
+                                KeyCode::Esc => reading_app.note_draft = None,
+                                KeyCode::Tab => {
+                                    if let Some(draft) = reading_app.note_draft.as_mut() {
+                                        draft.toggle_kind();
                                     }
                                 }
-                                KeyCode::Char('s') | KeyCode::Char('S') => {
-                                    reading_app.stop();
+                                KeyCode::Backspace => {
+                                    if let Some(draft) = reading_app.note_draft.as_mut() {
+                                        draft.text.pop();
+                                    }
                                 }
-                                KeyCode::Char('b') | KeyCode::Char('B') => {
-                                    if reading_app.timer_state != TimerState::Stopped {
-                                        reading_app.stop();
-                                    } else {
-                                        reading_app.save_page();
+                                KeyCode::Char(c) => {
+                                    if let Some(draft) = reading_app.note_draft.as_mut() {
+                                        draft.text.push(c);
                                     }
-                                    library = load_library()?;
-                                    app_mode = AppMode::BookSelection;
                                 }
-                                KeyCode::Char('q') | KeyCode::Char('Q') => {
-                                    if reading_app.timer_state != TimerState::Stopped {
-                                        reading_app.stop();
-                                    } else {
-                                        reading_app.save_page();
+                                _ => {}
+                            },
+                            // So does the rating prompt shown at 100%
+                            code if reading_app.rating_prompt.is_some() => {
+                                if let Some(prompt) = reading_app.rating_prompt.as_mut() {
+                                    match (prompt.stage, code) {
+                                        (RatingStage::Stars, KeyCode::Left | KeyCode::Down) => prompt.adjust(-0.5),
+                                        (RatingStage::Stars, KeyCode::Right | KeyCode::Up) => prompt.adjust(0.5),
+                                        (RatingStage::Stars, KeyCode::Char(c @ '1'..='5')) => {
+                                            prompt.rating = c.to_digit(10).unwrap_or(3) as f32;
+                                        }
+                                        (RatingStage::Stars, KeyCode::Enter) => prompt.stage = RatingStage::Review,
+                                        (RatingStage::Stars, KeyCode::Esc) => reading_app.rating_prompt = None,
+                                        (RatingStage::Review, KeyCode::Enter) => prompt.review.push('\n'),
+                                        (RatingStage::Review, KeyCode::Backspace) => {
+                                            prompt.review.pop();
+                                        }
+                                        (RatingStage::Review, KeyCode::Char(c)) => prompt.review.push(c),
+                                        (RatingStage::Review, KeyCode::Esc) => prompt.stage = RatingStage::Stars,
+                                        (_, KeyCode::Tab) => reading_app.save_rating(&mut store),
+                                        _ => {}
                                     }
-                                    break;
                                 }
-                                KeyCode::Up => {
-                                    reading_app.add_pages(10);
-                                    reading_app.save_page();
+                            }
+                            // And the next-in-series offer after finishing a book
+                            code if reading_app.next_up.is_some() => match code {
+                                KeyCode::Enter => match reading_app.next_up.take() {
+                                    Some(NextUp::Open { book_id, .. }) => {
+                                        if let Some(book) = store.library().book(book_id) {
+                                            reading_app.load_book(book);
+                                            selector.select_book(store.library(), book_id);
+                                        }
+                                    }
+                                    Some(NextUp::Add { series, authors }) => {
+                                        selector.start_add_next_in_series(&series, &authors);
+                                        app_mode = AppMode::BookSelection;
+                                    }
+                                    None => {}
+                                },
+                                KeyCode::Esc => reading_app.next_up = None,
+                                _ => {}
+                            },
+                            KeyCode::Char('w') | KeyCode::Char('W') => reading_app.start_note(),
+                            KeyCode::Char('f') | KeyCode::Char('F') => reading_app.toggle_focus(),
+                            KeyCode::Char('v') | KeyCode::Char('V') => {
+                                notes_panel = Some(NotesPanel::new(reading_app.book_id, false));
+                                app_mode = AppMode::Notes;
+                            }
+                            KeyCode::Char(' ') => {
+                                if reading_app.timer_state == TimerState::Running {
+                                    reading_app.pause();
+                                } else {
+                                    reading_app.start();
                                 }
-                                KeyCode::Down => {
-                                    reading_app.add_pages(-10);
-                                    reading_app.save_page();
+                            }
+                            KeyCode::Char('s') | KeyCode::Char('S') => {
+                                reading_app.stop(&mut store);
+                            }
+                            KeyCode::Char('b') | KeyCode::Char('B') => {
+                                if reading_app.timer_state != TimerState::Stopped {
+                                    reading_app.stop(&mut store);
+                                } else {
+                                    reading_app.save_page(&mut store);
                                 }
-                                KeyCode::Right => {
-                                    reading_app.increment_page();
-                                    reading_app.save_page();
+                                store.flush()?;
+                                // Finishing the book may have moved it to another group
+                                selector.select_book(store.library(), reading_app.book_id);
+                                // Stopping may have finished a series book; show what is next before leaving
+                                if reading_app.next_up.is_none() {
+                                    app_mode = AppMode::BookSelection;
                                 }
-                                KeyCode::Left => {
-                                    reading_app.decrement_page();
-                                    reading_app.save_page();
+                            }
+                            KeyCode::Char('q') | KeyCode::Char('Q') => {
+                                if reading_app.timer_state != TimerState::Stopped {
+                                    reading_app.stop(&mut store);
+                                } else {
+                                    reading_app.save_page(&mut store);
                                 }
-                                _ => {}
+                                if reading_app.next_up.is_none() {
+                                    break;
+                                }
+                            }
+                            KeyCode::Up => {
+                                reading_app.add_pages(reading_app.unit.large_step());
+                                reading_app.save_page(&mut store);
                             }
+                            KeyCode::Down => {
+                                reading_app.add_pages(-reading_app.unit.large_step());
+                                reading_app.save_page(&mut store);
+                            }
+                            KeyCode::Right => {
+                                reading_app.increment_page();
+                                reading_app.save_page(&mut store);
+                            }
+                            KeyCode::Char('n') | KeyCode::Char('N') => {
+                                reading_app.jump_to_next_chapter();
+                                reading_app.save_page(&mut store);
+                            }
+                            KeyCode::Left => {
+                                reading_app.decrement_page();
+                                reading_app.save_page(&mut store);
+                            }
+                            _ => {}
                         }
                     }
                 }
             }
         }
+
+        store.flush_if_due()?;
     }
 
-    disable_raw_mode()?;
-    stdout().execute(LeaveAlternateScreen)?;
+    // The checkpoint goes only once its session was stopped, as quitting from the reading screen does
+    if let Some(ref reading_app) = app
+        && lock_owner.is_none()
+    {
+        checkpointer.update(reading_app.active_session())?;
+    }
+    store.close()?;
+    drop(terminal_guard);
 
     Ok(())
 }
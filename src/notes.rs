@@ -0,0 +1,225 @@
+use ratatui::{
+    layout::{Alignment, Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, List, ListItem, ListState, Paragraph, Wrap},
+    Frame,
+};
+
+use crate::storage::{BookId, BookProgress, Library, Note, NoteKind};
+
+/// Scrollable list of one book's notes
+pub struct NotesPanel {
+    pub book_id: BookId,
+    pub selected_index: usize,
+    /// Opened from library search rather than the reading screen
+    pub from_search: bool,
+}
+
+impl NotesPanel {
+    pub fn new(book_id: BookId, from_search: bool) -> Self {
+        Self {
+            book_id,
+            selected_index: 0,
+            from_search,
+        }
+    }
+
+    pub fn select_next(&mut self, library: &Library) {
+        let count = library.book(self.book_id).map_or(0, |b| b.notes.len());
+        if count > 0 {
+            self.selected_index = (self.selected_index + 1).min(count - 1);
+        }
+    }
+
+    pub fn select_prev(&mut self) {
+        self.selected_index = self.selected_index.saturating_sub(1);
+    }
+
+    pub fn selected_note<'a>(&self, library: &'a Library) -> Option<&'a Note> {
+        library.book(self.book_id)?.notes.get(self.selected_index)
+    }
+
+    /// Keep the selection inside the list, e.g. after a note was deleted
+    pub fn clamp_selection(&mut self, library: &Library) {
+        let count = library.book(self.book_id).map_or(0, |b| b.notes.len());
+        self.selected_index = self.selected_index.min(count.saturating_sub(1));
+    }
+}
+
+/// Library-wide search over note text
+pub struct NoteSearch {
+    pub query: String,
+    pub selected_index: usize,
+}
+
+impl NoteSearch {
+    pub fn new() -> Self {
+        Self {
+            query: String::new(),
+            selected_index: 0,
+        }
+    }
+
+    pub fn results<'a>(&self, library: &'a Library) -> Vec<(&'a BookProgress, usize)> {
+        search_notes(library, &self.query)
+    }
+
+    pub fn select_next(&mut self, library: &Library) {
+        let count = self.results(library).len();
+        if count > 0 {
+            self.selected_index = (self.selected_index + 1).min(count - 1);
+        }
+    }
+
+    pub fn select_prev(&mut self) {
+        self.selected_index = self.selected_index.saturating_sub(1);
+    }
+
+    /// The book and note index of the selected result
+    pub fn selected<'a>(&self, library: &'a Library) -> Option<(&'a BookProgress, usize)> {
+        self.results(library).get(self.selected_index).copied()
+    }
+}
+
+/// Notes whose text contains `query`, ignoring case, as (book, note index) pairs
+fn search_notes<'a>(library: &'a Library, query: &str) -> Vec<(&'a BookProgress, usize)> {
+    let query = query.trim().to_lowercase();
+    if query.is_empty() {
+        return Vec::new();
+    }
+
+    library
+        .books
+        .iter()
+        .flat_map(|book| {
+            book.notes
+                .iter()
+                .enumerate()
+                .filter(|(_, note)| note.text.to_lowercase().contains(&query))
+                .map(move |(i, _)| (book, i))
+        })
+        .collect()
+}
+
+/// One-line summary: page, date and text, quotes in quotation marks
+fn note_summary(book: &BookProgress, note: &Note) -> String {
+    let text = match note.kind {
+        NoteKind::Note => note.text.clone(),
+        NoteKind::Quote => format!("\u{201c}{}\u{201d}", note.text),
+    };
+    format!(
+        "{:>8}  {}  {}",
+        book.unit.format_position(note.page),
+        note.created_at.format("%Y-%m-%d %H:%M"),
+        text
+    )
+}
+
+fn note_list<'a>(items: Vec<ListItem<'a>>, title: String) -> List<'a> {
+    List::new(items)
+        .block(Block::default().borders(Borders::ALL).title(title))
+        .highlight_style(Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD))
+}
+
+fn draw_note_detail(f: &mut Frame, note: Option<&Note>, area: Rect) {
+    let text = note.map(|n| n.text.clone()).unwrap_or_default();
+    let detail = Paragraph::new(text)
+        .wrap(Wrap { trim: false })
+        .block(Block::default().borders(Borders::ALL).title("Selected"));
+    f.render_widget(detail, area);
+}
+
+pub fn draw_notes(f: &mut Frame, library: &Library, panel: &NotesPanel) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .margin(2)
+        .constraints([
+            Constraint::Length(3), // Title
+            Constraint::Min(5),    // Notes
+            Constraint::Length(6), // Selected note
+            Constraint::Length(3), // Controls
+        ])
+        .split(f.area());
+
+    let book = library.book(panel.book_id);
+    let title = Paragraph::new(format!(
+        "Notes: {}",
+        book.map_or("", |b| b.book_title.as_str())
+    ))
+    .style(Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD))
+    .alignment(Alignment::Center)
+    .block(Block::default().borders(Borders::ALL));
+    f.render_widget(title, chunks[0]);
+
+    let items: Vec<ListItem> = match book {
+        Some(book) if !book.notes.is_empty() => book
+            .notes
+            .iter()
+            .map(|note| ListItem::new(note_summary(book, note)))
+            .collect(),
+        _ => vec![ListItem::new("No notes yet").style(Style::default().fg(Color::Gray))],
+    };
+    let count = book.map_or(0, |b| b.notes.len());
+    let mut state = ListState::default().with_selected((count > 0).then_some(panel.selected_index));
+    f.render_stateful_widget(note_list(items, format!("Notes ({})", count)), chunks[1], &mut state);
+
+    draw_note_detail(f, panel.selected_note(library), chunks[2]);
+
+    let controls = Paragraph::new("↑/↓: Scroll | D: Delete Note | Esc: Back")
+        .style(Style::default().fg(Color::Cyan))
+        .alignment(Alignment::Center)
+        .block(Block::default().borders(Borders::ALL).title("Controls"));
+    f.render_widget(controls, chunks[3]);
+}
+
+pub fn draw_note_search(f: &mut Frame, library: &Library, search: &NoteSearch) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .margin(2)
+        .constraints([
+            Constraint::Length(3), // Query
+            Constraint::Min(5),    // Results
+            Constraint::Length(6), // Selected note
+            Constraint::Length(3), // Controls
+        ])
+        .split(f.area());
+
+    let query = Paragraph::new(Line::from(vec![
+        Span::styled("Search notes: ", Style::default().fg(Color::Gray)),
+        Span::styled(
+            format!("{}_", search.query),
+            Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD),
+        ),
+    ]))
+    .block(Block::default().borders(Borders::ALL));
+    f.render_widget(query, chunks[0]);
+
+    let results = search.results(library);
+    let items: Vec<ListItem> = results
+        .iter()
+        .map(|(book, i)| {
+            ListItem::new(Line::from(vec![
+                Span::styled(book.book_title.clone(), Style::default().fg(Color::Cyan)),
+                Span::raw(note_summary(book, &book.notes[*i])),
+            ]))
+        })
+        .collect();
+    let mut state =
+        ListState::default().with_selected((!results.is_empty()).then_some(search.selected_index));
+    let title = if search.query.trim().is_empty() {
+        "Results".to_string()
+    } else {
+        format!("Results ({})", results.len())
+    };
+    f.render_stateful_widget(note_list(items, title), chunks[1], &mut state);
+
+    let selected = search.selected(library).map(|(book, i)| &book.notes[i]);
+    draw_note_detail(f, selected, chunks[2]);
+
+    let controls = Paragraph::new("Type to search | ↑/↓: Select | Enter: Open Book's Notes | Esc: Back")
+        .style(Style::default().fg(Color::Cyan))
+        .alignment(Alignment::Center)
+        .block(Block::default().borders(Borders::ALL).title("Controls"));
+    f.render_widget(controls, chunks[3]);
+}
@@ -0,0 +1,113 @@
+use ratatui::{
+    layout::{Alignment, Constraint, Direction, Layout},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Paragraph, Wrap},
+    Frame,
+};
+
+use crate::storage::CorruptLibrary;
+
+pub fn draw_recovery(f: &mut Frame, corrupt: &CorruptLibrary) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .margin(2)
+        .constraints([
+            Constraint::Length(3), // Title
+            Constraint::Min(8),    // Details
+            Constraint::Length(3), // Controls
+        ])
+        .split(f.area());
+
+    // Title
+    let title = Paragraph::new("Library Recovery")
+        .style(Style::default().fg(Color::Red).add_modifier(Modifier::BOLD))
+        .alignment(Alignment::Center)
+        .block(Block::default().borders(Borders::ALL));
+    f.render_widget(title, chunks[0]);
+
+    // Details
+    let backup_line = match &corrupt.backup {
+        Some(path) => Line::from(vec![
+            Span::styled("Backup available: ", Style::default().fg(Color::Gray)),
+            Span::styled(path.display().to_string(), Style::default().fg(Color::Green)),
+        ]),
+        None => Line::from(Span::styled(
+            "No usable backup was found.",
+            Style::default().fg(Color::Yellow),
+        )),
+    };
+
+    let headline = if corrupt.in_journal {
+        "The journal of recent changes could not be read. Your library file is intact and has not been modified."
+    } else {
+        "Your library file could not be read and has not been modified."
+    };
+    let details = vec![
+        Line::from(Span::styled(
+            headline,
+            Style::default().fg(Color::White).add_modifier(Modifier::BOLD),
+        )),
+        Line::from(""),
+        Line::from(vec![
+            Span::styled("Error: ", Style::default().fg(Color::Gray)),
+            Span::styled(corrupt.error.clone(), Style::default().fg(Color::Red)),
+        ]),
+        Line::from(vec![
+            Span::styled("Location: ", Style::default().fg(Color::Gray)),
+            Span::styled(
+                if corrupt.line == 0 {
+                    "unknown".to_string()
+                } else if corrupt.in_journal {
+                    format!("line {}", corrupt.line)
+                } else {
+                    format!("line {}, column {}", corrupt.line, corrupt.column)
+                },
+                Style::default().fg(Color::Cyan),
+            ),
+        ]),
+        Line::from(vec![
+            Span::styled("Copy saved to: ", Style::default().fg(Color::Gray)),
+            Span::styled(
+                corrupt.quarantined.display().to_string(),
+                Style::default().fg(Color::Cyan),
+            ),
+        ]),
+        Line::from(""),
+        if corrupt.in_journal {
+            Line::from(Span::styled(
+                "Changes before the bad line can be kept; the rest are lost.",
+                Style::default().fg(Color::Yellow),
+            ))
+        } else {
+            backup_line
+        },
+    ];
+
+    let details_widget = Paragraph::new(details)
+        .wrap(Wrap { trim: false })
+        .block(Block::default().borders(Borders::ALL).title("Details"));
+    f.render_widget(details_widget, chunks[1]);
+
+    // Controls
+    let controls = if corrupt.in_journal {
+        if corrupt.line > 1 {
+            format!(
+                "K: Keep Changes Before Line {} | D: Discard Journal | Q: Quit",
+                corrupt.line
+            )
+        } else {
+            "D: Discard Journal | Q: Quit".to_string()
+        }
+    } else if corrupt.backup.is_some() {
+        "R: Restore Backup | E: Start Empty Library | Q: Quit".to_string()
+    } else {
+        "E: Start Empty Library | Q: Quit".to_string()
+    };
+
+    let controls_widget = Paragraph::new(controls)
+        .style(Style::default().fg(Color::Cyan))
+        .alignment(Alignment::Center)
+        .block(Block::default().borders(Borders::ALL).title("Controls"));
+    f.render_widget(controls_widget, chunks[2]);
+}
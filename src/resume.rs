@@ -0,0 +1,84 @@
+use ratatui::{
+    layout::{Alignment, Constraint, Direction, Layout},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Paragraph, Wrap},
+    Frame,
+};
+
+use crate::checkpoint::ActiveSession;
+use crate::storage::{format_hms, BookProgress};
+
+pub fn draw_resume(f: &mut Frame, active: &ActiveSession, book: &BookProgress) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .margin(2)
+        .constraints([
+            Constraint::Length(3), // Title
+            Constraint::Min(8),    // Details
+            Constraint::Length(3), // Controls
+        ])
+        .split(f.area());
+
+    // Title
+    let title = Paragraph::new("Unfinished Session")
+        .style(Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD))
+        .alignment(Alignment::Center)
+        .block(Block::default().borders(Borders::ALL));
+    f.render_widget(title, chunks[0]);
+
+    // Details
+    let row = |label: &'static str, value: String| {
+        Line::from(vec![
+            Span::styled(label, Style::default().fg(Color::Gray)),
+            Span::styled(value, Style::default().fg(Color::Cyan)),
+        ])
+    };
+    let details = vec![
+        Line::from(Span::styled(
+            "Book Garden closed while the timer was still going.",
+            Style::default().fg(Color::White).add_modifier(Modifier::BOLD),
+        )),
+        Line::from(""),
+        row("Book: ", book.book_title.clone()),
+        row("Started: ", active.started_at().format("%Y-%m-%d %H:%M").to_string()),
+        row(
+            "Read: ",
+            format!(
+                "{} to {}",
+                book.unit.format_position(active.session_start_page),
+                book.unit.format_position(active.current_page)
+            ),
+        ),
+        row(
+            "Timer: ",
+            format!(
+                "{}{}",
+                format_hms(active.elapsed_secs as usize),
+                if active.running { "" } else { " (paused)" }
+            ),
+        ),
+        row("Last saved: ", active.checkpointed_at.format("%Y-%m-%d %H:%M:%S").to_string()),
+        Line::from(""),
+        Line::from(Span::styled(
+            if active.running {
+                "Resume continues the timer from the last save; time since then is not counted."
+            } else {
+                "Resume opens the session paused, as it was left."
+            },
+            Style::default().fg(Color::Gray),
+        )),
+    ];
+
+    let details_widget = Paragraph::new(details)
+        .wrap(Wrap { trim: false })
+        .block(Block::default().borders(Borders::ALL).title("Details"));
+    f.render_widget(details_widget, chunks[1]);
+
+    // Controls
+    let controls_widget = Paragraph::new("R: Resume | S: Save as Is | D: Discard | Q: Quit")
+        .style(Style::default().fg(Color::Cyan))
+        .alignment(Alignment::Center)
+        .block(Block::default().borders(Borders::ALL).title("Controls"));
+    f.render_widget(controls_widget, chunks[2]);
+}
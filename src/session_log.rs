@@ -0,0 +1,225 @@
+use chrono::{Local, NaiveDate, NaiveTime, TimeDelta, TimeZone};
+use ratatui::{
+    layout::{Alignment, Constraint, Direction, Layout},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Paragraph},
+    Frame,
+};
+use uuid::Uuid;
+
+use crate::form::{FieldForm, FormState};
+use crate::storage::{chapters_covered, BookId, BookProgress, Chapter, ProgressUnit, ReadingSession, TimeInterval};
+
+/// Fields of the session log form, in tab order
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LogField {
+    Date,
+    StartTime,
+    Duration,
+    StartPage,
+    EndPage,
+}
+
+impl LogField {
+    const ALL: [LogField; 5] = [
+        LogField::Date,
+        LogField::StartTime,
+        LogField::Duration,
+        LogField::StartPage,
+        LogField::EndPage,
+    ];
+
+    fn label(self, unit: ProgressUnit) -> &'static str {
+        match (self, unit) {
+            (LogField::Date, _) => "Date",
+            (LogField::StartTime, _) => "Start Time",
+            (LogField::Duration, _) => "Duration",
+            (LogField::StartPage, ProgressUnit::Pages) => "Start Page",
+            (LogField::EndPage, ProgressUnit::Pages) => "End Page",
+            (LogField::StartPage, _) => "Start Position",
+            (LogField::EndPage, _) => "End Position",
+        }
+    }
+
+    fn hint(self, unit: ProgressUnit) -> &'static str {
+        match (self, unit) {
+            (LogField::Date, _) => "Day you read, as YYYY-MM-DD",
+            (LogField::StartTime, _) => "When you started, as HH:MM",
+            (LogField::Duration, _) => "Minutes, or h:mm",
+            (LogField::StartPage | LogField::EndPage, ProgressUnit::Audio) => "Position as h:mm:ss",
+            (LogField::StartPage | LogField::EndPage, ProgressUnit::Percent) => "Percent read",
+            (LogField::StartPage | LogField::EndPage, _) => "Where the session started and ended",
+        }
+    }
+}
+
+/// Form for a reading session that happened away from the timer
+pub struct SessionLog {
+    pub book_id: BookId,
+    pub book_title: String,
+    unit: ProgressUnit,
+    total_pages: usize,
+    chapters: Vec<Chapter>,
+    state: FormState,
+}
+
+impl SessionLog {
+    /// A form for today, starting where the book was left off
+    pub fn new(book: &BookProgress) -> Self {
+        let mut log = Self {
+            book_id: book.id,
+            book_title: book.book_title.clone(),
+            unit: book.unit,
+            total_pages: book.total_pages,
+            chapters: book.chapters.clone(),
+            state: FormState::new(LogField::ALL.len()),
+        };
+        log.set_text(LogField::Date, Local::now().format("%Y-%m-%d").to_string());
+        log.set_text(LogField::StartPage, book.unit.format_position(book.current_page));
+        log
+    }
+}
+
+impl FieldForm for SessionLog {
+    type Field = LogField;
+    type Output = ReadingSession;
+
+    fn fields(&self) -> &[LogField] {
+        &LogField::ALL
+    }
+
+    fn state(&self) -> &FormState {
+        &self.state
+    }
+
+    fn state_mut(&mut self) -> &mut FormState {
+        &mut self.state
+    }
+
+    fn accept_char(&mut self, field: LogField, c: char) -> bool {
+        match field {
+            LogField::Date => c.is_ascii_digit() || c == '-',
+            LogField::StartTime | LogField::Duration => c.is_ascii_digit() || c == ':',
+            LogField::StartPage | LogField::EndPage => {
+                c.is_ascii_digit() || (c == ':' && self.unit == ProgressUnit::Audio)
+            }
+        }
+    }
+
+    fn parse(&self) -> Result<ReadingSession, (LogField, String)> {
+        let date = NaiveDate::parse_from_str(self.text(LogField::Date), "%Y-%m-%d")
+            .map_err(|_| (LogField::Date, "Date must look like 2024-05-31".to_string()))?;
+        let time = NaiveTime::parse_from_str(self.text(LogField::StartTime), "%H:%M")
+            .map_err(|_| (LogField::StartTime, "Start time must look like 20:15".to_string()))?;
+        let started = Local
+            .from_local_datetime(&date.and_time(time))
+            .earliest()
+            .ok_or_else(|| (LogField::StartTime, "That time does not exist on that day".to_string()))?;
+
+        let duration_secs = parse_duration(self.text(LogField::Duration))
+            .filter(|&secs| secs > 0)
+            .ok_or_else(|| (LogField::Duration, "Duration must be minutes or h:mm, above zero".to_string()))?;
+        let ended = i64::try_from(duration_secs)
+            .ok()
+            .and_then(TimeDelta::try_seconds)
+            .and_then(|duration| started.checked_add_signed(duration))
+            .filter(|&ended| ended <= Local::now())
+            .ok_or_else(|| (LogField::Duration, "The session would end in the future".to_string()))?;
+
+        let position = |field: LogField| {
+            match self.unit.parse_position(self.text(field)) {
+                Some(position) if position <= self.total_pages => Ok(position),
+                Some(_) => Err((
+                    field,
+                    format!("The book ends at {}", self.unit.format_position(self.total_pages)),
+                )),
+                None => Err((field, format!("{} is required", field.label(self.unit)))),
+            }
+        };
+        let start_page = position(LogField::StartPage)?;
+        let end_page = position(LogField::EndPage)?;
+        if end_page < start_page {
+            return Err((LogField::EndPage, "The session cannot end before it starts".to_string()));
+        }
+
+        Ok(ReadingSession {
+            id: Uuid::new_v4(),
+            book_title: self.book_title.clone(),
+            start_page,
+            end_page,
+            unit: self.unit,
+            duration_secs,
+            started_at: started,
+            timestamp: ended,
+            intervals: vec![TimeInterval { start: started, end: ended }],
+            focus_blocks: 0,
+            chapters: chapters_covered(&self.chapters, start_page, end_page),
+        })
+    }
+}
+
+/// Whole minutes, `h:mm` or `h:mm:ss`, as seconds
+pub fn parse_duration(text: &str) -> Option<u64> {
+    let parts = text
+        .split(':')
+        .map(|part| part.trim().parse::<u64>().ok())
+        .collect::<Option<Vec<u64>>>()?;
+    // Too many digits to count in seconds is rejected like any other bad value
+    match parts[..] {
+        [minutes] => minutes.checked_mul(60),
+        [hours, minutes] if minutes < 60 => hours.checked_mul(3600)?.checked_add(minutes * 60),
+        [hours, minutes, seconds] if minutes < 60 && seconds < 60 => {
+            hours.checked_mul(3600)?.checked_add(minutes * 60 + seconds)
+        }
+        _ => None,
+    }
+}
+
+pub fn draw_session_log(f: &mut Frame, log: &SessionLog) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .margin(2)
+        .constraints([
+            Constraint::Length(3),                               // Title
+            Constraint::Length(LogField::ALL.len() as u16 + 4), // Form
+            Constraint::Min(0),
+            Constraint::Length(3),                               // Controls
+        ])
+        .split(f.area());
+
+    let title = Paragraph::new(format!("Log Session: {}", log.book_title))
+        .style(Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD))
+        .alignment(Alignment::Center)
+        .block(Block::default().borders(Borders::ALL));
+    f.render_widget(title, chunks[0]);
+
+    let mut lines: Vec<Line> = LogField::ALL
+        .iter()
+        .enumerate()
+        .map(|(i, &field)| {
+            let color = if i == log.focus() { Color::Yellow } else { Color::White };
+            Line::from(Span::styled(
+                format!("{}: {}", field.label(log.unit), log.text_at(i)),
+                Style::default().fg(color),
+            ))
+        })
+        .collect();
+    lines.push(Line::from(""));
+    if let Some(error) = log.error() {
+        lines.push(Line::from(Span::styled(error.to_string(), Style::default().fg(Color::Red))));
+    }
+    let form = Paragraph::new(lines).block(Block::default().borders(Borders::ALL).title("Session"));
+    f.render_widget(form, chunks[1]);
+
+    let action = if log.on_last_field() { "Enter: Save" } else { "Enter: Next" };
+    let controls = Paragraph::new(format!(
+        "{} | {} | Tab/Shift+Tab: Move | Esc: Cancel",
+        log.field().hint(log.unit),
+        action
+    ))
+    .style(Style::default().fg(Color::Cyan))
+    .alignment(Alignment::Center)
+    .block(Block::default().borders(Borders::ALL).title("Controls"));
+    f.render_widget(controls, chunks[3]);
+}
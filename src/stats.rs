@@ -0,0 +1,96 @@
+use ratatui::{
+    layout::{Alignment, Constraint, Direction, Layout},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, List, ListItem, Paragraph},
+    Frame,
+};
+
+use crate::book_select::format_stars;
+use crate::storage::{rating_averages, Library};
+
+/// One list entry per name: average, stars, name and how many rated books it covers
+fn average_items(averages: Vec<(String, f32, usize)>, empty: &str) -> Vec<ListItem<'static>> {
+    if averages.is_empty() {
+        return vec![ListItem::new(empty.to_string()).style(Style::default().fg(Color::Gray))];
+    }
+
+    averages
+        .into_iter()
+        .map(|(name, average, count)| {
+            ListItem::new(Line::from(vec![
+                Span::styled(format!("{:.1} ", average), Style::default().fg(Color::Cyan)),
+                Span::styled(format_stars(average), Style::default().fg(Color::Yellow)),
+                Span::raw(format!("  {}", name)),
+                Span::styled(
+                    format!(" ({} {})", count, if count == 1 { "book" } else { "books" }),
+                    Style::default().fg(Color::Gray),
+                ),
+            ]))
+        })
+        .collect()
+}
+
+pub fn draw_library_stats(f: &mut Frame, library: &Library) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .margin(2)
+        .constraints([
+            Constraint::Length(3), // Title
+            Constraint::Length(3), // Summary
+            Constraint::Min(5),    // Averages
+            Constraint::Length(3), // Controls
+        ])
+        .split(f.area());
+
+    let title = Paragraph::new("Library Statistics")
+        .style(Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD))
+        .alignment(Alignment::Center)
+        .block(Block::default().borders(Borders::ALL));
+    f.render_widget(title, chunks[0]);
+
+    let ratings: Vec<f32> = library.books.iter().filter_map(|b| b.rating).collect();
+    let summary = if ratings.is_empty() {
+        Line::from(Span::styled("No rated books yet", Style::default().fg(Color::Gray)))
+    } else {
+        let average = ratings.iter().sum::<f32>() / ratings.len() as f32;
+        Line::from(vec![
+            Span::styled("Rated books: ", Style::default().fg(Color::Gray)),
+            Span::styled(ratings.len().to_string(), Style::default().fg(Color::Cyan)),
+            Span::styled("  Average: ", Style::default().fg(Color::Gray)),
+            Span::styled(
+                format!("{:.1} {}", average, format_stars(average)),
+                Style::default().fg(Color::Yellow),
+            ),
+        ])
+    };
+    let summary = Paragraph::new(summary)
+        .alignment(Alignment::Center)
+        .block(Block::default().borders(Borders::ALL));
+    f.render_widget(summary, chunks[1]);
+
+    let columns = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+        .split(chunks[2]);
+
+    let authors = average_items(
+        rating_averages(library, |b| &b.metadata.authors),
+        "No rated books with authors",
+    );
+    let authors = List::new(authors).block(Block::default().borders(Borders::ALL).title("By author"));
+    f.render_widget(authors, columns[0]);
+
+    let genres = average_items(
+        rating_averages(library, |b| &b.metadata.genres),
+        "No rated books with genres",
+    );
+    let genres = List::new(genres).block(Block::default().borders(Borders::ALL).title("By genre"));
+    f.render_widget(genres, columns[1]);
+
+    let controls = Paragraph::new("Esc: Back")
+        .style(Style::default().fg(Color::Cyan))
+        .alignment(Alignment::Center)
+        .block(Block::default().borders(Borders::ALL).title("Controls"));
+    f.render_widget(controls, chunks[3]);
+}
@@ -1,123 +1,1656 @@
-use chrono::{DateTime, Local};
+mod journal;
+mod json;
+mod sqlite;
+
+use chrono::{DateTime, Local, TimeDelta};
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashSet};
 use std::fs;
-use std::path::Path;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use uuid::Uuid;
+
+use crate::config::{BackendKind, Config};
+use crate::lock::LOCK_FILE;
+
+use json::library_path;
+pub use json::{discard_journal, reset_library, restore_backup, truncate_journal};
+pub(crate) use json::write_atomic;
+
+/// What a book's position is measured in
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum ProgressUnit {
+    #[default]
+    Pages,
+    /// Percent read, always out of 100
+    Percent,
+    /// E-reader locations
+    Locations,
+    /// Audiobook position in seconds
+    Audio,
+}
+
+impl ProgressUnit {
+    /// Next unit in the add/edit form
+    pub fn next(self) -> Self {
+        match self {
+            ProgressUnit::Pages => ProgressUnit::Percent,
+            ProgressUnit::Percent => ProgressUnit::Locations,
+            ProgressUnit::Locations => ProgressUnit::Audio,
+            ProgressUnit::Audio => ProgressUnit::Pages,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            ProgressUnit::Pages => "pages",
+            ProgressUnit::Percent => "percent",
+            ProgressUnit::Locations => "locations",
+            ProgressUnit::Audio => "audiobook",
+        }
+    }
+
+    /// How far ←/→ move the position
+    pub fn small_step(self) -> i64 {
+        match self {
+            ProgressUnit::Pages | ProgressUnit::Percent => 1,
+            ProgressUnit::Locations => 10,
+            ProgressUnit::Audio => 30,
+        }
+    }
+
+    /// How far ↑/↓ move the position
+    pub fn large_step(self) -> i64 {
+        match self {
+            ProgressUnit::Pages | ProgressUnit::Percent => 10,
+            ProgressUnit::Locations => 100,
+            ProgressUnit::Audio => 300,
+        }
+    }
+
+    /// A position or distance: "12 pages", "45%", "1:02:03"
+    pub fn format_amount(self, value: usize) -> String {
+        match self {
+            ProgressUnit::Pages => format!("{} pages", value),
+            ProgressUnit::Percent => format!("{}%", value),
+            ProgressUnit::Locations => format!("{} locations", value),
+            ProgressUnit::Audio => format_hms(value),
+        }
+    }
+
+    /// Position out of the total: "12/300 pages", "45%", "1:02:03/10:00:00"
+    pub fn format_fraction(self, current: usize, total: usize) -> String {
+        match self {
+            ProgressUnit::Pages => format!("{}/{} pages", current, total),
+            ProgressUnit::Percent => format!("{}%", current),
+            ProgressUnit::Locations => format!("{}/{} locations", current, total),
+            ProgressUnit::Audio => format!("{}/{}", format_hms(current), format_hms(total)),
+        }
+    }
+
+    /// A bare position as typed in the form: "412" or "1:02:03"
+    pub fn format_position(self, value: usize) -> String {
+        match self {
+            ProgressUnit::Audio => format_hms(value),
+            _ => value.to_string(),
+        }
+    }
+
+    /// Parse a position typed into the form; audiobooks take `h:mm:ss`, `mm:ss` or seconds
+    pub fn parse_position(self, text: &str) -> Option<usize> {
+        match self {
+            ProgressUnit::Audio => parse_hms(text.trim()),
+            _ => text.trim().parse().ok(),
+        }
+    }
+
+    /// Parse a book length typed into the form; percent is always out of 100
+    pub fn parse_total(self, text: &str) -> Option<usize> {
+        match self {
+            ProgressUnit::Percent => Some(100),
+            _ => self.parse_position(text),
+        }
+    }
+}
+
+/// Seconds as `h:mm:ss`
+pub fn format_hms(secs: usize) -> String {
+    format!("{}:{:02}:{:02}", secs / 3600, (secs % 3600) / 60, secs % 60)
+}
+
+fn parse_hms(text: &str) -> Option<usize> {
+    let parts: Vec<&str> = text.split(':').collect();
+    if parts.len() > 3 {
+        return None;
+    }
+
+    let mut secs: usize = 0;
+    for (i, part) in parts.iter().enumerate() {
+        let value: usize = part.parse().ok()?;
+        // Only the leading component may exceed 59
+        if i > 0 && value >= 60 {
+            return None;
+        }
+        // A long run of digits is rejected rather than wrapped
+        secs = secs.checked_mul(60)?.checked_add(value)?;
+    }
+    Some(secs)
+}
 
+/// A timed stretch of reading; positions are in the book's unit at the time
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct ReadingSession {
+    pub id: Uuid,
     pub book_title: String,
     pub start_page: usize,
     pub end_page: usize,
+    pub unit: ProgressUnit,
     pub duration_secs: u64,
+    /// When the timer was first started
+    pub started_at: DateTime<Local>,
+    /// When the session ended and was saved
     pub timestamp: DateTime<Local>,
+    /// Wall-clock stretches the timer was running, oldest first; the gaps are pauses
+    pub intervals: Vec<TimeInterval>,
+    /// Focus-mode blocks completed during the session
+    pub focus_blocks: u32,
+    /// Titles of the chapters the session read through
+    pub chapters: Vec<String>,
+}
+
+impl ReadingSession {
+    /// Change the session's length
+    ///
+    /// A shorter session loses running time from its end, as when the timer
+    /// was left going; a longer one starts earlier, as when it was started late.
+    pub fn set_duration(&mut self, secs: u64) {
+        let running: u64 = self.intervals.iter().map(TimeInterval::secs).sum();
+        let (mut kept, _) = split_intervals(&self.intervals, secs);
+        if secs > running
+            && let Some(first) = kept.first_mut()
+            && let Some(start) = i64::try_from(secs - running)
+                .ok()
+                .and_then(TimeDelta::try_seconds)
+                .and_then(|extra| first.start.checked_sub_signed(extra))
+        {
+            first.start = start;
+        }
+        if let (Some(first), Some(last)) = (kept.first(), kept.last()) {
+            self.started_at = first.start;
+            self.timestamp = last.end;
+        }
+        self.intervals = kept;
+        self.duration_secs = secs;
+    }
+}
+
+/// A stretch of wall-clock time during which the timer was running
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub struct TimeInterval {
+    pub start: DateTime<Local>,
+    pub end: DateTime<Local>,
+}
+
+impl TimeInterval {
+    pub fn secs(&self) -> u64 {
+        (self.end - self.start).num_seconds().max(0) as u64
+    }
+}
+
+/// The first `secs` of running time, and whatever runs after it
+fn split_intervals(intervals: &[TimeInterval], secs: u64) -> (Vec<TimeInterval>, Vec<TimeInterval>) {
+    let mut before = Vec::new();
+    let mut after = Vec::new();
+    let mut left = secs;
+    for interval in intervals {
+        let length = interval.secs();
+        if left == 0 {
+            after.push(*interval);
+        } else if left >= length {
+            before.push(*interval);
+            left -= length;
+        } else {
+            let cut = interval.start + chrono::Duration::seconds(left as i64);
+            before.push(TimeInterval { start: interval.start, end: cut });
+            after.push(TimeInterval { start: cut, end: interval.end });
+            left = 0;
+        }
+    }
+    (before, after)
+}
+
+/// Persistent identifier of a book, independent of its position in the library
+pub type BookId = Uuid;
+
+/// Descriptive details about a book, all optional
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct BookMetadata {
+    pub authors: Vec<String>,
+    /// ISBN-10 or ISBN-13 without separators, checksum already validated
+    pub isbn: Option<String>,
+    pub publisher: Option<String>,
+    pub year: Option<i32>,
+    pub language: Option<String>,
+    pub genres: Vec<String>,
+    pub series: Option<Series>,
+}
+
+/// A book's place in a series
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Series {
+    pub name: String,
+    /// 1-based position in the series
+    pub number: u32,
+    /// How many books the series has, if known
+    pub total: Option<u32>,
+}
+
+impl Series {
+    /// "Book 3 of 7", or "Book 3" when the length of the series is unknown
+    pub fn position_label(&self) -> String {
+        match self.total {
+            Some(total) => format!("Book {} of {}", self.number, total),
+            None => format!("Book {}", self.number),
+        }
+    }
+
+    pub fn same_series(&self, other: &Series) -> bool {
+        self.name.to_lowercase() == other.name.to_lowercase()
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum NoteKind {
+    Note,
+    /// Text copied from the book
+    Quote,
+}
+
+/// A thought or quotation captured while reading
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Note {
+    pub id: Uuid,
+    pub kind: NoteKind,
+    pub text: String,
+    /// Position in the book's unit
+    pub page: usize,
+    pub created_at: DateTime<Local>,
+    /// The session that was running when the note was taken
+    pub session_id: Option<Uuid>,
+}
+
+/// An entry in a book's table of contents
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Chapter {
+    pub title: String,
+    /// Position the chapter starts at, in the book's unit
+    pub start_page: usize,
+}
+
+/// Index of the chapter containing `page`; `chapters` must be sorted by start
+pub fn chapter_index_at(chapters: &[Chapter], page: usize) -> Option<usize> {
+    chapters.iter().rposition(|c| c.start_page <= page)
+}
+
+/// Titles of the chapters touched while moving from `from` to `to`
+pub fn chapters_covered(chapters: &[Chapter], from: usize, to: usize) -> Vec<String> {
+    let (low, high) = (from.min(to), from.max(to));
+    let first = chapter_index_at(chapters, low).unwrap_or(0);
+    match chapter_index_at(chapters, high) {
+        Some(last) if last >= first => chapters[first..=last].iter().map(|c| c.title.clone()).collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// The user-editable description of a book, as entered in the add/edit form
+#[derive(Debug, Clone)]
+pub struct BookDetails {
+    pub book_title: String,
+    pub unit: ProgressUnit,
+    pub total_pages: usize,
+    pub first_page: usize,
+    pub last_page: Option<usize>,
+    pub idle_minutes: Option<u32>,
+    pub metadata: BookMetadata,
+}
+
+/// The stretch of a book that counts towards progress, e.g. skipping front matter
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ReadingRange {
+    pub first: usize,
+    pub last: usize,
+}
+
+impl ReadingRange {
+    /// Fraction of the range read at `page`, from 0.0 to 1.0
+    pub fn progress(&self, page: usize) -> f64 {
+        if self.last <= self.first {
+            return 0.0;
+        }
+        self.read_between(self.first, page) as f64 / (self.last - self.first) as f64
+    }
+
+    /// How much of the range lies between two positions
+    pub fn read_between(&self, from: usize, to: usize) -> usize {
+        let clamp = |page: usize| page.clamp(self.first, self.last);
+        clamp(to).saturating_sub(clamp(from))
+    }
+
+    pub fn remaining(&self, page: usize) -> usize {
+        self.last - page.clamp(self.first, self.last)
+    }
+
+    /// Whether the range is narrower than the whole book
+    pub fn is_partial(&self, total: usize) -> bool {
+        self.first > 0 || self.last < total
+    }
 }
 
+/// Where a book is in its reading lifecycle
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(tag = "state", rename_all = "snake_case")]
+pub enum ReadingStatus {
+    WantToRead,
+    Reading,
+    Finished,
+    /// Did not finish
+    Abandoned { reason: String },
+}
+
+impl ReadingStatus {
+    /// Order of the groups in the library list
+    pub fn rank(&self) -> usize {
+        match self {
+            ReadingStatus::Reading => 0,
+            ReadingStatus::WantToRead => 1,
+            ReadingStatus::Finished => 2,
+            ReadingStatus::Abandoned { .. } => 3,
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            ReadingStatus::WantToRead => "Want to Read",
+            ReadingStatus::Reading => "Reading",
+            ReadingStatus::Finished => "Finished",
+            ReadingStatus::Abandoned { .. } => "Abandoned",
+        }
+    }
+}
+
+/// An earlier read of a book, kept when a re-read starts
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ReadingRun {
+    pub status: ReadingStatus,
+    pub started_at: Option<DateTime<Local>>,
+    pub finished_at: Option<DateTime<Local>>,
+    /// Page the run ended on
+    pub current_page: usize,
+    pub sessions: Vec<ReadingSession>,
+}
+
+/// A book and its current read; earlier reads live in `previous_runs`
+///
+/// `total_pages` and `current_page` are measured in `unit`, so they may be
+/// locations, seconds of audio or percent rather than pages.
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct BookProgress {
+    pub id: BookId,
     pub book_title: String,
+    pub unit: ProgressUnit,
     pub total_pages: usize,
     pub current_page: usize,
+    /// Where the counted reading starts, e.g. after front matter
+    pub first_page: usize,
+    /// Where the counted reading ends; `None` means the end of the book
+    pub last_page: Option<usize>,
+    /// Table of contents, sorted by start page
+    pub chapters: Vec<Chapter>,
+    /// Notes and quotes from every read, oldest first
+    pub notes: Vec<Note>,
+    /// Stars from 0.5 to 5 in half-star steps
+    pub rating: Option<f32>,
+    pub review: Option<String>,
+    /// Minutes without a key press before the timer asks if you are still
+    /// reading; `Some(0)` turns it off, `None` uses the configured default
+    pub idle_minutes: Option<u32>,
+    pub metadata: BookMetadata,
+    pub status: ReadingStatus,
+    /// When the first session was recorded
+    pub started_at: Option<DateTime<Local>>,
+    /// When the book was finished or abandoned
+    pub finished_at: Option<DateTime<Local>>,
     pub sessions: Vec<ReadingSession>,
+    /// Completed or abandoned reads, oldest first
+    pub previous_runs: Vec<ReadingRun>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+impl BookProgress {
+    /// Keep the current page inside the book, e.g. after `total_pages` shrank
+    pub fn clamp_current_page(&mut self) {
+        self.current_page = self.current_page.min(self.total_pages);
+    }
+
+    pub fn reading_range(&self) -> ReadingRange {
+        let last = self.last_page.unwrap_or(self.total_pages).min(self.total_pages);
+        ReadingRange {
+            first: self.first_page.min(last),
+            last,
+        }
+    }
+
+    /// Append a finished session and move the book to the page it ended on
+    ///
+    /// The first session starts the book; one that reaches the end of the
+    /// reading range finishes it. Reading an abandoned book picks it back up.
+    pub fn record_session(&mut self, session: ReadingSession) {
+        self.current_page = session.end_page;
+
+        if matches!(self.status, ReadingStatus::WantToRead | ReadingStatus::Abandoned { .. }) {
+            self.status = ReadingStatus::Reading;
+            self.started_at.get_or_insert(session.started_at);
+            self.finished_at = None;
+        }
+        let range = self.reading_range();
+        if self.status == ReadingStatus::Reading
+            && range.last > range.first
+            && self.current_page >= range.last
+        {
+            self.status = ReadingStatus::Finished;
+            self.finished_at = Some(session.timestamp);
+        }
+
+        self.sessions.push(session);
+    }
+
+    /// Insert a session logged after the fact, keeping sessions in chronological order
+    ///
+    /// Only a session newer than every other one moves the current position.
+    pub fn log_session(&mut self, session: ReadingSession) {
+        let index = self.sessions.partition_point(|s| s.timestamp <= session.timestamp);
+        if index == self.sessions.len() {
+            self.record_session(session);
+            return;
+        }
+
+        if self.started_at.is_none_or(|at| session.started_at < at) {
+            self.started_at = Some(session.started_at);
+        }
+        self.sessions.insert(index, session);
+    }
+
+    /// Mark the book as not finished, giving up at the current page
+    pub fn abandon(&mut self, reason: String) {
+        self.status = ReadingStatus::Abandoned { reason };
+        self.finished_at = Some(Local::now());
+    }
+
+    /// Switch units, keeping the same fraction of the book read
+    ///
+    /// Chapter starts and note positions are converted the same way, so they
+    /// still point at the same place in the book.
+    pub fn change_unit(&mut self, unit: ProgressUnit, total: usize) {
+        if unit != self.unit && self.total_pages > 0 {
+            let old_total = self.total_pages as f64;
+            let convert = |position: usize| (position as f64 / old_total * total as f64).round() as usize;
+            self.current_page = convert(self.current_page);
+            for chapter in &mut self.chapters {
+                chapter.start_page = convert(chapter.start_page);
+            }
+            for note in &mut self.notes {
+                note.page = convert(note.page);
+            }
+        }
+        self.unit = unit;
+        self.total_pages = total;
+        self.clamp_current_page();
+    }
+
+    /// 1 for the first read, 2 for the first re-read, and so on
+    pub fn read_number(&self) -> usize {
+        self.previous_runs.len() + 1
+    }
+
+    /// Sessions from every run, oldest run first
+    pub fn all_sessions(&self) -> impl Iterator<Item = &ReadingSession> {
+        self.previous_runs
+            .iter()
+            .flat_map(|run| run.sessions.iter())
+            .chain(self.sessions.iter())
+    }
+
+    /// Archive the current read and start the book over from the first page
+    pub fn start_rerun(&mut self) {
+        let first = self.reading_range().first;
+        let run = ReadingRun {
+            status: std::mem::replace(&mut self.status, ReadingStatus::WantToRead),
+            started_at: self.started_at.take(),
+            finished_at: self.finished_at.take(),
+            current_page: std::mem::replace(&mut self.current_page, first),
+            sessions: std::mem::take(&mut self.sessions),
+        };
+        self.previous_runs.push(run);
+    }
+
+    /// Where a session lives: its run's index in `previous_runs` (`None` for the
+    /// current read) and its index within that run
+    fn find_session(&self, session_id: Uuid) -> Option<(Option<usize>, usize)> {
+        if let Some(index) = self.sessions.iter().position(|s| s.id == session_id) {
+            return Some((None, index));
+        }
+        self.previous_runs.iter().enumerate().find_map(|(run, previous)| {
+            let index = previous.sessions.iter().position(|s| s.id == session_id)?;
+            Some((Some(run), index))
+        })
+    }
+
+    fn run_sessions_mut(&mut self, run: Option<usize>) -> &mut Vec<ReadingSession> {
+        match run {
+            Some(run) => &mut self.previous_runs[run].sessions,
+            None => &mut self.sessions,
+        }
+    }
+
+    /// Correct a recorded session's pages and duration
+    pub fn edit_session(&mut self, session_id: Uuid, start_page: usize, end_page: usize, duration_secs: u64) {
+        let Some((run, index)) = self.find_session(session_id) else {
+            return;
+        };
+        let chapters = chapters_covered(&self.chapters, start_page, end_page);
+        let sessions = self.run_sessions_mut(run);
+        let latest = index + 1 == sessions.len();
+        let session = &mut sessions[index];
+        session.start_page = start_page;
+        session.end_page = end_page;
+        session.set_duration(duration_secs);
+        session.chapters = chapters;
+
+        if run.is_none() {
+            self.sync_with_sessions(latest);
+        }
+    }
+
+    pub fn delete_session(&mut self, session_id: Uuid) {
+        let Some((run, index)) = self.find_session(session_id) else {
+            return;
+        };
+        let sessions = self.run_sessions_mut(run);
+        sessions.remove(index);
+        let latest = index == sessions.len();
+
+        if run.is_none() {
+            self.sync_with_sessions(latest);
+        }
+    }
+
+    /// Split a session in two at `at_page`, the first part lasting `first_secs`
+    ///
+    /// The first part keeps the session's id so notes taken during it stay linked,
+    /// and its focus blocks, since which part they fell in is not recorded.
+    pub fn split_session(&mut self, session_id: Uuid, at_page: usize, first_secs: u64) {
+        let Some((run, index)) = self.find_session(session_id) else {
+            return;
+        };
+        let original = self.run_sessions_mut(run)[index].clone();
+        let second_secs = original.duration_secs.saturating_sub(first_secs);
+        let (first_intervals, second_intervals) = split_intervals(&original.intervals, first_secs);
+        let split_at = second_intervals
+            .first()
+            .map_or(original.timestamp - chrono::Duration::seconds(second_secs as i64), |i| i.start);
+        let first = ReadingSession {
+            end_page: at_page,
+            duration_secs: first_secs,
+            timestamp: first_intervals.last().map_or(split_at, |i| i.end),
+            intervals: first_intervals,
+            chapters: chapters_covered(&self.chapters, original.start_page, at_page),
+            ..original.clone()
+        };
+        let second = ReadingSession {
+            id: Uuid::new_v4(),
+            start_page: at_page,
+            duration_secs: second_secs,
+            started_at: split_at,
+            focus_blocks: 0,
+            intervals: second_intervals,
+            chapters: chapters_covered(&self.chapters, at_page, original.end_page),
+            ..original
+        };
+
+        let sessions = self.run_sessions_mut(run);
+        sessions[index] = first;
+        sessions.insert(index + 1, second);
+    }
+
+    /// Bring the current read's position, dates and status back in line after
+    /// its sessions changed
+    ///
+    /// The position only follows the sessions when the newest one was changed,
+    /// so page moves made since then are kept otherwise.
+    fn sync_with_sessions(&mut self, latest_changed: bool) {
+        let range = self.reading_range();
+        if latest_changed {
+            self.current_page = self.sessions.last().map_or(range.first, |s| s.end_page);
+        }
+        self.started_at = self.sessions.first().map(|s| s.started_at);
+
+        match self.status {
+            ReadingStatus::Finished if self.current_page < range.last => {
+                self.status = ReadingStatus::Reading;
+                self.finished_at = None;
+            }
+            ReadingStatus::Reading if range.last > range.first && self.current_page >= range.last => {
+                self.status = ReadingStatus::Finished;
+                self.finished_at = self.sessions.last().map(|s| s.timestamp);
+            }
+            _ => {}
+        }
+        if self.status == ReadingStatus::Reading && self.sessions.is_empty() {
+            self.status = ReadingStatus::WantToRead;
+        }
+    }
+
+    /// Bring an abandoned book back into the reading list
+    pub fn resume(&mut self) {
+        self.status = if self.sessions.is_empty() {
+            ReadingStatus::WantToRead
+        } else {
+            ReadingStatus::Reading
+        };
+        self.finished_at = None;
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Library {
+    pub schema_version: u32,
+    /// Sequence number of the last journal entry included in this document
+    pub journal_seq: u64,
     pub books: Vec<BookProgress>,
 }
 
-const STORAGE_DIR: &str = "reading_data";
-const LIBRARY_FILE: &str = "reading_data/library.json";
+impl Library {
+    pub fn new() -> Self {
+        Self {
+            schema_version: SCHEMA_VERSION,
+            journal_seq: 0,
+            books: Vec::new(),
+        }
+    }
 
-/// Initialize storage directory
-pub fn init_storage() -> std::io::Result<()> {
-    let path = Path::new(STORAGE_DIR);
-    if !path.exists() {
-        fs::create_dir(path)?;
+    pub fn book(&self, id: BookId) -> Option<&BookProgress> {
+        self.books.iter().find(|b| b.id == id)
     }
+
+    fn book_mut(&mut self, id: BookId) -> Option<&mut BookProgress> {
+        self.books.iter_mut().find(|b| b.id == id)
+    }
+}
+
+/// Version of the library document written by this build
+pub const SCHEMA_VERSION: u32 = 14;
+
+/// A single upgrade step on the raw JSON document
+type Migration = fn(&mut Value) -> Result<(), String>;
+
+/// `MIGRATIONS[n]` upgrades a version `n` document to version `n + 1`
+const MIGRATIONS: [Migration; SCHEMA_VERSION as usize] = [
+    migrate_v0_to_v1,
+    migrate_v1_to_v2,
+    migrate_v2_to_v3,
+    migrate_v3_to_v4,
+    migrate_v4_to_v5,
+    migrate_v5_to_v6,
+    migrate_v6_to_v7,
+    migrate_v7_to_v8,
+    migrate_v8_to_v9,
+    migrate_v9_to_v10,
+    migrate_v10_to_v11,
+    migrate_v11_to_v12,
+    migrate_v12_to_v13,
+    migrate_v13_to_v14,
+];
+
+/// Version 0 (no `schema_version`) books may lack a persistent `id`
+fn migrate_v0_to_v1(doc: &mut Value) -> Result<(), String> {
+    for_each_book(doc, |book| {
+        book.entry("id")
+            .or_insert_with(|| Value::String(Uuid::new_v4().to_string()));
+    })
+}
+
+/// Version 2 snapshots record how much of the journal they contain
+fn migrate_v1_to_v2(doc: &mut Value) -> Result<(), String> {
+    let doc = doc.as_object_mut().ok_or("library is not an object")?;
+    doc.entry("journal_seq").or_insert(0.into());
     Ok(())
 }
 
-/// Load library from file
-pub fn load_library() -> std::io::Result<Library> {
-    init_storage()?;
+/// Calls `f` on every book object in the document
+fn for_each_book(doc: &mut Value, mut f: impl FnMut(&mut serde_json::Map<String, Value>)) -> Result<(), String> {
+    let books = doc
+        .get_mut("books")
+        .and_then(Value::as_array_mut)
+        .ok_or("missing \"books\" array")?;
+
+    for book in books {
+        f(book.as_object_mut().ok_or("book is not an object")?);
+    }
+    Ok(())
+}
+
+/// Version 3 books carry a `metadata` object
+fn migrate_v2_to_v3(doc: &mut Value) -> Result<(), String> {
+    for_each_book(doc, |book| {
+        book.entry("metadata").or_insert_with(|| {
+            serde_json::json!({
+                "authors": [],
+                "isbn": null,
+                "publisher": null,
+                "year": null,
+                "language": null,
+                "genres": [],
+            })
+        });
+    })
+}
+
+/// Version 4 books carry a reading status, inferred here from their progress
+fn migrate_v3_to_v4(doc: &mut Value) -> Result<(), String> {
+    for_each_book(doc, |book| {
+        let page = |key: &str| book.get(key).and_then(Value::as_u64).unwrap_or(0);
+        let (current_page, total_pages) = (page("current_page"), page("total_pages"));
+
+        let timestamps: Vec<Value> = book
+            .get("sessions")
+            .and_then(Value::as_array)
+            .map(|sessions| sessions.iter().filter_map(|s| s.get("timestamp").cloned()).collect())
+            .unwrap_or_default();
+        let first = timestamps.first().cloned().unwrap_or(Value::Null);
+        let last = timestamps.last().cloned().unwrap_or(Value::Null);
+
+        let (state, finished_at) = if total_pages > 0 && current_page >= total_pages {
+            ("finished", last)
+        } else if current_page == 0 && timestamps.is_empty() {
+            ("want_to_read", Value::Null)
+        } else {
+            ("reading", Value::Null)
+        };
+
+        book.entry("status").or_insert_with(|| serde_json::json!({ "state": state }));
+        book.entry("started_at").or_insert(first);
+        book.entry("finished_at").or_insert(finished_at);
+    })
+}
+
+/// Version 5 books can hold earlier reads; existing books have none
+fn migrate_v4_to_v5(doc: &mut Value) -> Result<(), String> {
+    for_each_book(doc, |book| {
+        book.entry("previous_runs").or_insert_with(|| Value::Array(Vec::new()));
+    })
+}
+
+/// Version 6 books and sessions carry a progress unit; everything so far was pages
+fn migrate_v5_to_v6(doc: &mut Value) -> Result<(), String> {
+    for_each_book(doc, |book| {
+        book.entry("unit").or_insert_with(|| Value::from("pages"));
+        for_each_session(book, |session| {
+            session.entry("unit").or_insert_with(|| Value::from("pages"));
+        });
+    })
+}
 
-    let path = Path::new(LIBRARY_FILE);
-    if path.exists() {
-        let data = fs::read_to_string(path)?;
-        match serde_json::from_str(&data) {
-            Ok(library) => Ok(library),
-            Err(_) => Ok(Library { books: Vec::new() }),
+/// Calls `f` on every session of a book object, including earlier runs
+fn for_each_session(
+    book: &mut serde_json::Map<String, Value>,
+    mut f: impl FnMut(&mut serde_json::Map<String, Value>),
+) {
+    let mut visit = |sessions: Option<&mut Value>| {
+        sessions
+            .and_then(Value::as_array_mut)
+            .into_iter()
+            .flatten()
+            .filter_map(Value::as_object_mut)
+            .for_each(&mut f);
+    };
+
+    for run in book
+        .get_mut("previous_runs")
+        .and_then(Value::as_array_mut)
+        .into_iter()
+        .flatten()
+    {
+        visit(run.get_mut("sessions"));
+    }
+    visit(book.get_mut("sessions"));
+}
+
+/// Version 7 books have a reading range; existing books cover the whole book
+fn migrate_v6_to_v7(doc: &mut Value) -> Result<(), String> {
+    for_each_book(doc, |book| {
+        book.entry("first_page").or_insert_with(|| Value::from(0));
+        book.entry("last_page").or_insert(Value::Null);
+    })
+}
+
+/// Version 8 books have a table of contents and sessions list the chapters they covered
+fn migrate_v7_to_v8(doc: &mut Value) -> Result<(), String> {
+    for_each_book(doc, |book| {
+        book.entry("chapters").or_insert_with(|| Value::Array(Vec::new()));
+        for_each_session(book, |session| {
+            session.entry("chapters").or_insert_with(|| Value::Array(Vec::new()));
+        });
+    })
+}
+
+/// Version 9 books hold notes, and sessions get an `id` notes can refer to
+fn migrate_v8_to_v9(doc: &mut Value) -> Result<(), String> {
+    for_each_book(doc, |book| {
+        book.entry("notes").or_insert_with(|| Value::Array(Vec::new()));
+        for_each_session(book, |session| {
+            session
+                .entry("id")
+                .or_insert_with(|| Value::String(Uuid::new_v4().to_string()));
+        });
+    })
+}
+
+/// Version 10 books can carry a rating and review
+fn migrate_v9_to_v10(doc: &mut Value) -> Result<(), String> {
+    for_each_book(doc, |book| {
+        book.entry("rating").or_insert(Value::Null);
+        book.entry("review").or_insert(Value::Null);
+    })
+}
+
+/// Version 11 metadata can place a book in a series
+fn migrate_v10_to_v11(doc: &mut Value) -> Result<(), String> {
+    for_each_book(doc, |book| {
+        if let Some(metadata) = book.get_mut("metadata").and_then(Value::as_object_mut) {
+            metadata.entry("series").or_insert(Value::Null);
         }
-    } else {
-        Ok(Library { books: Vec::new() })
+    })
+}
+
+/// Version 12 books can override the idle threshold
+fn migrate_v11_to_v12(doc: &mut Value) -> Result<(), String> {
+    for_each_book(doc, |book| {
+        book.entry("idle_minutes").or_insert(Value::Null);
+    })
+}
+
+/// Version 13 sessions record when they started and when the timer ran
+///
+/// Earlier sessions only kept their end, so they are taken to have run
+/// without a break for their whole duration.
+fn migrate_v12_to_v13(doc: &mut Value) -> Result<(), String> {
+    for_each_book(doc, |book| {
+        for_each_session(book, |session| {
+            let Some(end) = session.get("timestamp").cloned() else {
+                return;
+            };
+            let secs = session.get("duration_secs").and_then(Value::as_i64).unwrap_or(0);
+            let start = end
+                .as_str()
+                .and_then(|end| DateTime::parse_from_rfc3339(end).ok())
+                .map_or(end.clone(), |end| {
+                    Value::String((end - chrono::Duration::seconds(secs)).to_rfc3339())
+                });
+            session.entry("started_at").or_insert_with(|| start.clone());
+            session
+                .entry("intervals")
+                .or_insert_with(|| serde_json::json!([{ "start": start, "end": end }]));
+        });
+    })
+}
+
+/// Version 14 sessions count their focus-mode blocks
+fn migrate_v13_to_v14(doc: &mut Value) -> Result<(), String> {
+    for_each_book(doc, |book| {
+        for_each_session(book, |session| {
+            session.entry("focus_blocks").or_insert_with(|| Value::from(0));
+        });
+    })
+}
+
+/// Bring a raw document up to `SCHEMA_VERSION`, one step at a time
+fn migrate(doc: &mut Value, from: u32) -> Result<(), String> {
+    for (version, step) in MIGRATIONS.iter().enumerate().skip(from as usize) {
+        step(doc).map_err(|e| format!("migrating from version {}: {}", version, e))?;
+    }
+    if let Some(obj) = doc.as_object_mut() {
+        obj.insert("schema_version".to_string(), SCHEMA_VERSION.into());
     }
+    Ok(())
 }
 
-/// Save library to file
-pub fn save_library(library: &Library) -> std::io::Result<()> {
-    init_storage()?;
-    let json = serde_json::to_string_pretty(library)?;
-    fs::write(LIBRARY_FILE, json)?;
+/// Bring a journal entry written at schema version `from` up to `SCHEMA_VERSION`
+///
+/// The book, session or note the entry carries is wrapped in a one-book
+/// document, so the same `MIGRATIONS` steps upgrade it as upgrade the snapshot.
+fn migrate_entry(entry: &mut Value, from: u32) -> Result<(), String> {
+    let entry = entry.as_object_mut().ok_or("entry is not an object")?;
+    let Some(key) = ["book", "session", "note"].into_iter().find(|key| entry.contains_key(*key)) else {
+        entry.insert("schema_version".to_string(), SCHEMA_VERSION.into());
+        return Ok(());
+    };
+    let payload = entry.remove(key).unwrap_or_default();
+    let book = match key {
+        "book" => payload,
+        "session" => serde_json::json!({ "sessions": [payload] }),
+        _ => serde_json::json!({ "notes": [payload] }),
+    };
+
+    let mut doc = serde_json::json!({ "schema_version": from, "books": [book] });
+    migrate(&mut doc, from)?;
+    let mut book = doc["books"][0].take();
+    let payload = match key {
+        "book" => book,
+        "session" => book["sessions"][0].take(),
+        _ => book["notes"][0].take(),
+    };
+    entry.insert(key.to_string(), payload);
+    entry.insert("schema_version".to_string(), SCHEMA_VERSION.into());
     Ok(())
 }
 
-/// Add a new book to the library
-pub fn add_book(book_title: String, total_pages: usize) -> std::io::Result<usize> {
-    let mut library = load_library()?;
+/// Data directory used before the XDG location, relative to the working directory
+const LEGACY_DIR: &str = "reading_data";
 
-    library.books.push(BookProgress {
-        book_title,
-        total_pages,
-        current_page: 0,
-        sessions: Vec::new(),
-    });
+/// Details about a library file that could not be parsed
+#[derive(Debug, Clone)]
+pub struct CorruptLibrary {
+    pub error: String,
+    pub line: usize,
+    pub column: usize,
+    pub quarantined: PathBuf,
+    pub backup: Option<PathBuf>,
+    /// Only the journal failed to read; the snapshot it applies to is intact
+    pub in_journal: bool,
+}
 
-    let index = library.books.len() - 1;
-    save_library(&library)?;
-    Ok(index)
+/// Result of opening the library at startup
+pub enum LibraryStatus {
+    Loaded(Library),
+    Corrupt(CorruptLibrary),
+    /// Written by a newer build using the given schema version
+    TooNew(u32),
 }
 
-/// Update book in library
-pub fn update_book(book_index: usize, progress: &BookProgress) -> std::io::Result<()> {
-    let mut library = load_library()?;
+/// Outcome of parsing a library document
+enum Parsed {
+    Ok {
+        library: Library,
+        /// Original schema version if the document had to be upgraded
+        migrated_from: Option<u32>,
+    },
+    Corrupt(serde_json::Error),
+    TooNew(u32),
+}
 
-    if book_index < library.books.len() {
-        library.books[book_index] = progress.clone();
-        save_library(&library)?;
+/// Initialize storage directory
+pub fn init_storage(dir: &Path) -> std::io::Result<()> {
+    if !dir.exists() {
+        fs::create_dir_all(dir)?;
     }
-
     Ok(())
 }
 
-/// Save reading session for a specific book
-pub fn save_session(book_index: usize, session: &ReadingSession) -> std::io::Result<()> {
-    let mut library = load_library()?;
+/// Move a `./reading_data` library left by older versions into `dir`
+///
+/// Only happens when `dir` has no library of its own yet. Returns the legacy
+/// directory if anything was moved.
+pub fn migrate_legacy_dir(dir: &Path) -> std::io::Result<Option<PathBuf>> {
+    let legacy = Path::new(LEGACY_DIR);
+    if !library_path(legacy).exists() || library_path(dir).exists() || journal::journal_path(dir).exists() {
+        return Ok(None);
+    }
+    if let (Ok(from), Ok(to)) = (legacy.canonicalize(), dir.canonicalize())
+        && from == to
+    {
+        return Ok(None);
+    }
 
-    if book_index < library.books.len() {
-        library.books[book_index].current_page = session.end_page;
-        library.books[book_index].sessions.push(session.clone());
-        save_library(&library)?;
+    init_storage(dir)?;
+    for entry in fs::read_dir(legacy)? {
+        let from = entry?.path();
+        // `dir` already holds this instance's lock; a leftover one must not replace it
+        if !from.is_file() || from.file_name().is_some_and(|name| name == LOCK_FILE) {
+            continue;
+        }
+        let to = dir.join(from.file_name().unwrap_or_default());
+        // rename fails across filesystems, so fall back to copy and remove
+        if fs::rename(&from, &to).is_err() {
+            fs::copy(&from, &to)?;
+            fs::remove_file(&from)?;
+        }
     }
+    // Leave the directory behind if it still holds anything we did not move
+    let _ = fs::remove_dir(legacy);
 
-    Ok(())
+    Ok(Some(legacy.to_path_buf()))
 }
 
-/// Save just the current page without a session
-pub fn save_current_page(book_index: usize, current_page: usize) -> std::io::Result<()> {
-    let mut library = load_library()?;
+/// Check the schema version of a raw document and upgrade it if it is older
+fn parse_document(mut doc: Value) -> Parsed {
+    let version = match doc.get("schema_version") {
+        None => 0,
+        Some(v) => match v.as_u64() {
+            Some(v) => v as u32,
+            None => {
+                let e = serde::de::Error::custom("schema_version is not a number");
+                return Parsed::Corrupt(e);
+            }
+        },
+    };
+    if version > SCHEMA_VERSION {
+        return Parsed::TooNew(version);
+    }
 
-    if book_index < library.books.len() {
-        library.books[book_index].current_page = current_page;
-        save_library(&library)?;
+    if version < SCHEMA_VERSION
+        && let Err(e) = migrate(&mut doc, version)
+    {
+        return Parsed::Corrupt(serde::de::Error::custom(e));
     }
 
-    Ok(())
+    match serde_json::from_value(doc) {
+        Ok(library) => Parsed::Ok {
+            library,
+            migrated_from: (version < SCHEMA_VERSION).then_some(version),
+        },
+        Err(e) => Parsed::Corrupt(e),
+    }
+}
+
+/// Persistence for the library
+///
+/// `LibraryStore` keeps the authoritative copy in memory and forwards every
+/// change here; implementations decide how and when it reaches the disk.
+pub trait LibraryBackend {
+    /// Read the whole library, upgrading older data as needed
+    fn load(&mut self) -> std::io::Result<LibraryStatus>;
+    fn add_book(&mut self, book: &BookProgress) -> std::io::Result<()>;
+    /// Record a finished session, also moving the book to the page it ended on
+    fn append_session(&mut self, book_id: BookId, session: &ReadingSession) -> std::io::Result<()>;
+    fn set_current_page(&mut self, book_id: BookId, current_page: usize) -> std::io::Result<()>;
+    fn add_note(&mut self, book_id: BookId, note: &Note) -> std::io::Result<()>;
+    fn update_book(&mut self, book: &BookProgress) -> std::io::Result<()>;
+    /// Remove a book together with its sessions
+    fn delete_book(&mut self, book_id: BookId) -> std::io::Result<()>;
+    /// Make every change so far durable
+    fn flush(&mut self) -> std::io::Result<()>;
+    /// Flush and tidy up before the program exits
+    fn close(&mut self) -> std::io::Result<()> {
+        self.flush()
+    }
 }
 
-/// Get total reading statistics
-pub fn get_statistics(progress: &BookProgress) -> (u64, usize) {
-    let total_time: u64 = progress.sessions.iter().map(|s| s.duration_secs).sum();
-    let total_sessions = progress.sessions.len();
+/// Stands in for the real backend when the library is open read-only
+///
+/// Changes still apply to the in-memory library but are never written.
+struct ReadOnlyBackend;
+
+impl LibraryBackend for ReadOnlyBackend {
+    fn load(&mut self) -> std::io::Result<LibraryStatus> {
+        Err(io::Error::new(io::ErrorKind::PermissionDenied, "library is open read-only"))
+    }
+
+    fn add_book(&mut self, _book: &BookProgress) -> std::io::Result<()> {
+        Ok(())
+    }
+
+    fn append_session(&mut self, _book_id: BookId, _session: &ReadingSession) -> std::io::Result<()> {
+        Ok(())
+    }
+
+    fn set_current_page(&mut self, _book_id: BookId, _current_page: usize) -> std::io::Result<()> {
+        Ok(())
+    }
+
+    fn add_note(&mut self, _book_id: BookId, _note: &Note) -> std::io::Result<()> {
+        Ok(())
+    }
+
+    fn update_book(&mut self, _book: &BookProgress) -> std::io::Result<()> {
+        Ok(())
+    }
+
+    fn delete_book(&mut self, _book_id: BookId) -> std::io::Result<()> {
+        Ok(())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Read the library without writing anything, for an instance that does not hold the lock
+///
+/// `None` if the library cannot be read as it is.
+pub fn peek_library(kind: BackendKind, dir: &Path) -> Option<Library> {
+    match kind {
+        BackendKind::Json => json::peek_library(dir),
+        BackendKind::Sqlite => sqlite::peek_library(dir),
+    }
+}
+
+/// Create the backend of the given kind for a data directory
+pub fn open_backend(kind: BackendKind, dir: &Path, config: &Config) -> Box<dyn LibraryBackend> {
+    match kind {
+        BackendKind::Json => Box::new(json::JsonBackend::new(dir.to_path_buf(), config.backup_count)),
+        BackendKind::Sqlite => Box::new(sqlite::SqliteBackend::new(dir.to_path_buf())),
+    }
+}
+
+/// Where a backend keeps its data, for messages
+pub fn backend_path(kind: BackendKind, dir: &Path) -> PathBuf {
+    match kind {
+        BackendKind::Json => json::library_path(dir),
+        BackendKind::Sqlite => sqlite::database_path(dir),
+    }
+}
+
+fn load_loaded(backend: &mut dyn LibraryBackend, kind: BackendKind) -> std::io::Result<Library> {
+    match backend.load()? {
+        LibraryStatus::Loaded(library) => Ok(library),
+        LibraryStatus::Corrupt(details) => Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("{} library is corrupt: {}", kind, details.error),
+        )),
+        LibraryStatus::TooNew(version) => Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("{} library uses newer schema version {}", kind, version),
+        )),
+    }
+}
+
+/// Copy every book from one backend to another, returning how many were copied
+///
+/// The destination must be empty. After copying, the destination is read back
+/// and compared with the source so that nothing is lost silently.
+pub fn migrate_storage(dir: &Path, config: &Config, from: BackendKind, to: BackendKind) -> std::io::Result<usize> {
+    if from == to {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, "source and destination are the same backend"));
+    }
+
+    let source = load_loaded(open_backend(from, dir, config).as_mut(), from)?;
+
+    let mut destination = open_backend(to, dir, config);
+    let existing = load_loaded(destination.as_mut(), to)?;
+    if !existing.books.is_empty() {
+        return Err(io::Error::new(
+            io::ErrorKind::AlreadyExists,
+            format!("{} already contains {} books", backend_path(to, dir).display(), existing.books.len()),
+        ));
+    }
+
+    for book in &source.books {
+        destination.add_book(book)?;
+    }
+    destination.close()?;
+
+    let copied = load_loaded(open_backend(to, dir, config).as_mut(), to)?;
+    if serde_json::to_value(&copied.books)? != serde_json::to_value(&source.books)? {
+        return Err(io::Error::other(format!(
+            "{} does not match the source after copying",
+            backend_path(to, dir).display()
+        )));
+    }
+
+    Ok(source.books.len())
+}
+
+/// In-memory owner of the library
+///
+/// All mutations happen on the loaded `Library` and are passed on to the
+/// backend. Page changes are held back and only handed over by `flush`, which
+/// callers trigger on quit and book switch, and by `flush_if_due` once edits
+/// have settled for `FLUSH_DELAY`.
+pub struct LibraryStore {
+    backend: Box<dyn LibraryBackend>,
+    read_only: bool,
+    library: Library,
+    pending_pages: HashSet<BookId>,
+    last_change: Option<Instant>,
+}
+
+/// How long page edits may sit in memory before being written out
+const FLUSH_DELAY: Duration = Duration::from_secs(2);
+
+impl LibraryStore {
+    pub fn new(backend: Box<dyn LibraryBackend>, library: Library) -> Self {
+        Self {
+            backend,
+            read_only: false,
+            library,
+            pending_pages: HashSet::new(),
+            last_change: None,
+        }
+    }
+
+    pub fn library(&self) -> &Library {
+        &self.library
+    }
+
+    /// Stop writing anything, e.g. because another instance holds the lock
+    pub fn set_read_only(&mut self) {
+        self.backend = Box::new(ReadOnlyBackend);
+        self.read_only = true;
+    }
+
+    pub fn is_read_only(&self) -> bool {
+        self.read_only
+    }
+
+    /// Load the library again from the backend, e.g. after restoring a backup
+    pub fn reload(&mut self) -> std::io::Result<()> {
+        self.library = match self.backend.load()? {
+            LibraryStatus::Loaded(library) => library,
+            _ => return Err(io::Error::new(io::ErrorKind::InvalidData, "library could not be loaded")),
+        };
+        self.pending_pages.clear();
+        self.last_change = None;
+        Ok(())
+    }
+
+    /// Write pending changes to the backend and make them durable
+    pub fn flush(&mut self) -> std::io::Result<()> {
+        for book_id in self.pending_pages.drain() {
+            if let Some(book) = self.library.book(book_id) {
+                self.backend.set_current_page(book_id, book.current_page)?;
+            }
+        }
+        self.backend.flush()?;
+        self.last_change = None;
+        Ok(())
+    }
 
-    (total_time, total_sessions)
+    /// Flush everything and let the backend tidy up before exiting
+    pub fn close(&mut self) -> std::io::Result<()> {
+        self.flush()?;
+        self.backend.close()
+    }
+
+    /// Flush once no changes have arrived for `FLUSH_DELAY`
+    pub fn flush_if_due(&mut self) -> std::io::Result<()> {
+        match self.last_change {
+            Some(changed) if changed.elapsed() >= FLUSH_DELAY => self.flush(),
+            _ => Ok(()),
+        }
+    }
+
+    /// Add a new book to the library
+    pub fn add_book(&mut self, details: BookDetails) -> std::io::Result<BookId> {
+        let book = BookProgress {
+            id: Uuid::new_v4(),
+            book_title: details.book_title,
+            unit: details.unit,
+            total_pages: details.total_pages,
+            current_page: details.first_page,
+            first_page: details.first_page,
+            last_page: details.last_page,
+            chapters: Vec::new(),
+            notes: Vec::new(),
+            rating: None,
+            review: None,
+            idle_minutes: details.idle_minutes,
+            metadata: details.metadata,
+            status: ReadingStatus::WantToRead,
+            started_at: None,
+            finished_at: None,
+            sessions: Vec::new(),
+            previous_runs: Vec::new(),
+        };
+
+        let id = book.id;
+        self.backend.add_book(&book)?;
+        self.library.books.push(book);
+        self.flush()?;
+        Ok(id)
+    }
+
+    /// Update book in library
+    pub fn update_book(&mut self, book_id: BookId, progress: &BookProgress) -> std::io::Result<()> {
+        if let Some(book) = self.library.book_mut(book_id) {
+            *book = BookProgress {
+                id: book_id,
+                ..progress.clone()
+            };
+            book.clamp_current_page();
+            self.pending_pages.remove(&book_id);
+            self.backend.update_book(book)?;
+            self.flush()?;
+        }
+
+        Ok(())
+    }
+
+    /// Change a book's title, length, unit and metadata
+    pub fn edit_book(&mut self, book_id: BookId, details: BookDetails) -> std::io::Result<()> {
+        if let Some(book) = self.library.book(book_id) {
+            let mut edited = BookProgress {
+                book_title: details.book_title,
+                first_page: details.first_page,
+                last_page: details.last_page,
+                idle_minutes: details.idle_minutes,
+                metadata: details.metadata,
+                ..book.clone()
+            };
+            edited.change_unit(details.unit, details.total_pages);
+            self.update_book(book_id, &edited)?;
+        }
+
+        Ok(())
+    }
+
+    /// Remove a book and all of its sessions
+    pub fn delete_book(&mut self, book_id: BookId) -> std::io::Result<()> {
+        if self.library.book(book_id).is_some() {
+            self.library.books.retain(|b| b.id != book_id);
+            self.pending_pages.remove(&book_id);
+            self.backend.delete_book(book_id)?;
+            self.flush()?;
+        }
+
+        Ok(())
+    }
+
+    /// Save reading session for a specific book
+    pub fn save_session(&mut self, book_id: BookId, session: &ReadingSession) -> std::io::Result<()> {
+        if let Some(book) = self.library.book_mut(book_id) {
+            book.record_session(session.clone());
+            self.pending_pages.remove(&book_id);
+            self.backend.append_session(book_id, session)?;
+            self.flush()?;
+        }
+
+        Ok(())
+    }
+
+    /// Mark a book as did-not-finish, or resume it when `reason` is `None`
+    pub fn set_abandoned(&mut self, book_id: BookId, reason: Option<String>) -> std::io::Result<()> {
+        if let Some(book) = self.library.book(book_id) {
+            let mut book = book.clone();
+            match reason {
+                Some(reason) => book.abandon(reason),
+                None => book.resume(),
+            }
+            self.update_book(book_id, &book)?;
+        }
+
+        Ok(())
+    }
+
+    /// Attach a note or quote to a book
+    pub fn add_note(&mut self, book_id: BookId, note: Note) -> std::io::Result<()> {
+        if let Some(book) = self.library.book_mut(book_id) {
+            self.backend.add_note(book_id, &note)?;
+            book.notes.push(note);
+            self.flush()?;
+        }
+
+        Ok(())
+    }
+
+    pub fn delete_note(&mut self, book_id: BookId, note_id: Uuid) -> std::io::Result<()> {
+        if let Some(book) = self.library.book(book_id) {
+            let mut book = book.clone();
+            book.notes.retain(|n| n.id != note_id);
+            self.update_book(book_id, &book)?;
+        }
+
+        Ok(())
+    }
+
+    /// Rate and review a book; the rating is rounded to the nearest half star
+    pub fn set_review(&mut self, book_id: BookId, rating: f32, review: Option<String>) -> std::io::Result<()> {
+        if let Some(book) = self.library.book(book_id) {
+            let book = BookProgress {
+                rating: Some(((rating * 2.0).round() / 2.0).clamp(0.5, 5.0)),
+                review,
+                ..book.clone()
+            };
+            self.update_book(book_id, &book)?;
+        }
+
+        Ok(())
+    }
+
+    /// Replace a book's table of contents
+    pub fn set_chapters(&mut self, book_id: BookId, mut chapters: Vec<Chapter>) -> std::io::Result<()> {
+        if let Some(book) = self.library.book(book_id) {
+            chapters.sort_by_key(|c| c.start_page);
+            let book = BookProgress {
+                chapters,
+                ..book.clone()
+            };
+            self.update_book(book_id, &book)?;
+        }
+
+        Ok(())
+    }
+
+    /// Archive the book's current read and start it again from the beginning
+    pub fn start_rerun(&mut self, book_id: BookId) -> std::io::Result<()> {
+        if let Some(book) = self.library.book(book_id) {
+            let mut book = book.clone();
+            book.start_rerun();
+            self.update_book(book_id, &book)?;
+        }
+
+        Ok(())
+    }
+
+    /// Add a session entered by hand for reading done away from the timer
+    pub fn log_session(&mut self, book_id: BookId, session: ReadingSession) -> std::io::Result<()> {
+        if let Some(book) = self.library.book(book_id) {
+            let mut book = book.clone();
+            book.log_session(session);
+            self.update_book(book_id, &book)?;
+        }
+
+        Ok(())
+    }
+
+    /// Change one of a book's sessions, keeping the position and status consistent
+    fn change_sessions(&mut self, book_id: BookId, f: impl FnOnce(&mut BookProgress)) -> std::io::Result<()> {
+        if let Some(book) = self.library.book(book_id) {
+            let mut book = book.clone();
+            f(&mut book);
+            self.update_book(book_id, &book)?;
+        }
+
+        Ok(())
+    }
+
+    pub fn edit_session(
+        &mut self,
+        book_id: BookId,
+        session_id: Uuid,
+        start_page: usize,
+        end_page: usize,
+        duration_secs: u64,
+    ) -> std::io::Result<()> {
+        self.change_sessions(book_id, |book| {
+            book.edit_session(session_id, start_page, end_page, duration_secs)
+        })
+    }
+
+    pub fn delete_session(&mut self, book_id: BookId, session_id: Uuid) -> std::io::Result<()> {
+        self.change_sessions(book_id, |book| book.delete_session(session_id))
+    }
+
+    pub fn split_session(
+        &mut self,
+        book_id: BookId,
+        session_id: Uuid,
+        at_page: usize,
+        first_secs: u64,
+    ) -> std::io::Result<()> {
+        self.change_sessions(book_id, |book| book.split_session(session_id, at_page, first_secs))
+    }
+
+    /// Save just the current page without a session
+    pub fn save_current_page(&mut self, book_id: BookId, current_page: usize) {
+        if let Some(book) = self.library.book_mut(book_id)
+            && book.current_page != current_page
+        {
+            book.current_page = current_page;
+            self.pending_pages.insert(book_id);
+            self.last_change = Some(Instant::now());
+        }
+    }
+}
+
+/// Time spent and sessions logged over some stretch of reading
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ReadingStats {
+    pub total_time_secs: u64,
+    pub total_sessions: usize,
+}
+
+impl ReadingStats {
+    fn from_sessions<'a>(sessions: impl Iterator<Item = &'a ReadingSession>) -> Self {
+        let mut stats = Self::default();
+        for session in sessions {
+            stats.add_session(session.duration_secs);
+        }
+        stats
+    }
+
+    pub fn add_session(&mut self, duration_secs: u64) {
+        self.total_time_secs += duration_secs;
+        self.total_sessions += 1;
+    }
+}
+
+/// The lowest-numbered want-to-read book that follows `series` in the same series
+pub fn next_in_series<'a>(library: &'a Library, series: &Series) -> Option<&'a BookProgress> {
+    library
+        .books
+        .iter()
+        .filter(|book| book.status == ReadingStatus::WantToRead)
+        .filter_map(|book| Some((book, book.metadata.series.as_ref()?)))
+        .filter(|(_, other)| other.same_series(series) && other.number > series.number)
+        .min_by_key(|(_, other)| other.number)
+        .map(|(book, _)| book)
+}
+
+/// Average rating per name (author or genre), best first, with the number of rated books
+pub fn rating_averages<'a>(
+    library: &'a Library,
+    names: impl Fn(&'a BookProgress) -> &'a [String],
+) -> Vec<(String, f32, usize)> {
+    let mut totals: BTreeMap<&str, (f32, usize)> = BTreeMap::new();
+    for book in &library.books {
+        if let Some(rating) = book.rating {
+            for name in names(book) {
+                let entry = totals.entry(name.as_str()).or_default();
+                entry.0 += rating;
+                entry.1 += 1;
+            }
+        }
+    }
+
+    let mut averages: Vec<(String, f32, usize)> = totals
+        .into_iter()
+        .map(|(name, (sum, count))| (name.to_string(), sum / count as f32, count))
+        .collect();
+    averages.sort_by(|a, b| b.1.total_cmp(&a.1));
+    averages
+}
+
+/// Get reading statistics for the current run and across every run
+pub fn get_statistics(progress: &BookProgress) -> (ReadingStats, ReadingStats) {
+    let run = ReadingStats::from_sessions(progress.sessions.iter());
+    let lifetime = ReadingStats::from_sessions(progress.all_sessions());
+
+    (run, lifetime)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    /// A library from before `schema_version` existed goes through every migration
+    #[test]
+    fn migrates_unversioned_library() {
+        let doc = json!({
+            "books": [{
+                "book_title": "Dune",
+                "total_pages": 300,
+                "current_page": 300,
+                "sessions": [{
+                    "book_title": "Dune",
+                    "start_page": 0,
+                    "end_page": 300,
+                    "duration_secs": 3600,
+                    "timestamp": "2024-05-01T20:00:00+02:00",
+                }],
+            }],
+        });
+
+        let Parsed::Ok { library, migrated_from } = parse_document(doc) else {
+            panic!("unversioned library was not migrated");
+        };
+        assert_eq!(migrated_from, Some(0));
+        assert_eq!(library.schema_version, SCHEMA_VERSION);
+        assert_eq!(library.journal_seq, 0);
+        let book = &library.books[0];
+        assert_eq!(book.status, ReadingStatus::Finished);
+        assert_eq!(book.unit, ProgressUnit::Pages);
+        assert_eq!(book.reading_range().first, 0);
+        assert!(book.previous_runs.is_empty());
+        assert_eq!(book.metadata, BookMetadata::default());
+        let session = &book.sessions[0];
+        assert_eq!((session.timestamp - session.started_at).num_seconds(), 3600);
+        assert_eq!(session.intervals.len(), 1);
+        assert_eq!(session.focus_blocks, 0);
+    }
+
+    /// Journal entries are upgraded on their own, without a library around them
+    #[test]
+    fn migrates_old_journal_entry() {
+        let mut entry = json!({
+            "seq": 3,
+            "at": "2024-05-01T20:00:00+02:00",
+            "schema_version": 5,
+            "event": "session_recorded",
+            "book_id": "11cddd93-edf3-43b0-a41e-5412c379a931",
+            "session": {
+                "book_title": "Dune",
+                "start_page": 10,
+                "end_page": 40,
+                "duration_secs": 900,
+                "timestamp": "2024-05-01T20:00:00+02:00",
+            },
+        });
+
+        migrate_entry(&mut entry, 5).unwrap();
+
+        assert_eq!(entry["schema_version"], SCHEMA_VERSION);
+        assert_eq!(entry["seq"], 3);
+        assert_eq!(entry["event"], "session_recorded");
+        let session: ReadingSession = serde_json::from_value(entry["session"].clone()).unwrap();
+        assert_eq!(session.unit, ProgressUnit::Pages);
+        assert_eq!((session.start_page, session.end_page), (10, 40));
+        assert_eq!((session.timestamp - session.started_at).num_seconds(), 900);
+        assert_eq!(session.focus_blocks, 0);
+        assert!(session.chapters.is_empty());
+    }
+
+    /// A newer build's library is refused rather than read with missing fields
+    #[test]
+    fn refuses_newer_schema() {
+        let doc = json!({ "schema_version": SCHEMA_VERSION + 1, "journal_seq": 0, "books": [] });
+
+        let Parsed::TooNew(version) = parse_document(doc) else {
+            panic!("newer library was not refused");
+        };
+        assert_eq!(version, SCHEMA_VERSION + 1);
+    }
 }
@@ -0,0 +1,233 @@
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use serde_json::Value;
+
+use super::{BookId, BookProgress, Library, Note, ReadingSession, SCHEMA_VERSION, migrate_entry};
+
+const JOURNAL_FILE: &str = "journal.jsonl";
+/// Compacted journals are kept here as the audit trail
+const HISTORY_DIR: &str = "history";
+
+pub fn journal_path(dir: &Path) -> PathBuf {
+    dir.join(JOURNAL_FILE)
+}
+
+/// A single change to the library
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum LibraryEvent {
+    BookAdded {
+        book: BookProgress,
+    },
+    PageSet {
+        book_id: BookId,
+        previous_page: usize,
+        current_page: usize,
+    },
+    SessionRecorded {
+        book_id: BookId,
+        session: ReadingSession,
+    },
+    NoteAdded {
+        book_id: BookId,
+        note: Note,
+    },
+    BookEdited {
+        book: BookProgress,
+    },
+    BookDeleted {
+        book_id: BookId,
+        book_title: String,
+    },
+}
+
+impl LibraryEvent {
+    pub fn apply(&self, library: &mut Library) {
+        match self {
+            LibraryEvent::BookAdded { book } => library.books.push(book.clone()),
+            LibraryEvent::PageSet { book_id, current_page, .. } => {
+                if let Some(book) = library.book_mut(*book_id) {
+                    book.current_page = *current_page;
+                }
+            }
+            LibraryEvent::SessionRecorded { book_id, session } => {
+                if let Some(book) = library.book_mut(*book_id) {
+                    book.record_session(session.clone());
+                }
+            }
+            LibraryEvent::NoteAdded { book_id, note } => {
+                if let Some(book) = library.book_mut(*book_id) {
+                    book.notes.push(note.clone());
+                }
+            }
+            LibraryEvent::BookEdited { book } => {
+                if let Some(existing) = library.book_mut(book.id) {
+                    *existing = book.clone();
+                }
+            }
+            LibraryEvent::BookDeleted { book_id, .. } => {
+                library.books.retain(|b| b.id != *book_id);
+            }
+        }
+    }
+}
+
+/// One line of the journal
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct JournalEntry {
+    pub seq: u64,
+    pub at: DateTime<Local>,
+    pub schema_version: u32,
+    #[serde(flatten)]
+    pub event: LibraryEvent,
+}
+
+impl JournalEntry {
+    pub fn new(seq: u64, event: LibraryEvent) -> Self {
+        Self {
+            seq,
+            at: Local::now(),
+            schema_version: SCHEMA_VERSION,
+            event,
+        }
+    }
+}
+
+/// Where and why the journal could not be read
+#[derive(Debug, Clone)]
+pub struct BadJournal {
+    /// Line of the first unreadable entry, or 0 if the file could not be read at all
+    pub line: usize,
+    pub error: String,
+}
+
+/// Read every entry in the journal, upgrading entries from older schema versions
+///
+/// A final line without a trailing newline is an append that was cut short by
+/// a crash and is skipped, even if it happens to parse; any other unreadable
+/// line is an error.
+pub fn read_journal(dir: &Path) -> Result<Vec<JournalEntry>, BadJournal> {
+    let path = journal_path(dir);
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let data = fs::read_to_string(&path).map_err(|e| BadJournal {
+        line: 0,
+        error: e.to_string(),
+    })?;
+    let complete = data.ends_with('\n');
+    let lines: Vec<&str> = data.lines().collect();
+
+    let mut entries = Vec::new();
+    for (i, line) in lines.iter().enumerate() {
+        if line.trim().is_empty() || (i + 1 == lines.len() && !complete) {
+            continue;
+        }
+        match parse_entry(line) {
+            Ok(entry) => entries.push(entry),
+            Err(error) => {
+                return Err(BadJournal {
+                    line: i + 1,
+                    error: format!("{} line {}: {}", JOURNAL_FILE, i + 1, error),
+                });
+            }
+        }
+    }
+    Ok(entries)
+}
+
+fn parse_entry(line: &str) -> Result<JournalEntry, String> {
+    let mut entry: Value = serde_json::from_str(line).map_err(|e| e.to_string())?;
+    let version = entry
+        .get("schema_version")
+        .and_then(Value::as_u64)
+        .ok_or("missing schema_version")? as u32;
+    if version > SCHEMA_VERSION {
+        return Err(format!("written by newer schema version {}", version));
+    }
+    if version < SCHEMA_VERSION {
+        migrate_entry(&mut entry, version)?;
+    }
+    serde_json::from_value(entry).map_err(|e| e.to_string())
+}
+
+/// Cut off a final line that a crash left without its newline
+///
+/// `read_journal` skips such a line; removing it keeps the next append from
+/// landing on the end of it and turning it into a bad line mid-journal.
+pub fn drop_torn_tail(dir: &Path) -> std::io::Result<()> {
+    let path = journal_path(dir);
+    let Ok(data) = fs::read(&path) else {
+        return Ok(());
+    };
+    if data.is_empty() || data.ends_with(b"\n") {
+        return Ok(());
+    }
+    let keep = data.iter().rposition(|&b| b == b'\n').map_or(0, |i| i + 1);
+    let file = OpenOptions::new().write(true).open(&path)?;
+    file.set_len(keep as u64)?;
+    file.sync_data()
+}
+
+/// Append entries to the journal and wait until they are on disk
+pub fn append_journal(dir: &Path, entries: &[JournalEntry]) -> std::io::Result<()> {
+    if entries.is_empty() {
+        return Ok(());
+    }
+
+    let mut data = String::new();
+    for entry in entries {
+        data.push_str(&serde_json::to_string(entry)?);
+        data.push('\n');
+    }
+
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(journal_path(dir))?;
+    file.write_all(data.as_bytes())?;
+    file.sync_data()
+}
+
+/// Move the journal into the history directory once its entries are in the snapshot
+pub fn archive_journal(dir: &Path, first_seq: u64, last_seq: u64) -> std::io::Result<()> {
+    let path = journal_path(dir);
+    if !path.exists() {
+        return Ok(());
+    }
+
+    let history = dir.join(HISTORY_DIR);
+    fs::create_dir_all(&history)?;
+    let archived = history.join(format!(
+        "journal-{}-{:09}-{:09}.jsonl",
+        Local::now().format("%Y%m%d-%H%M%S"),
+        first_seq,
+        last_seq
+    ));
+    fs::rename(&path, archived)?;
+
+    #[cfg(unix)]
+    File::open(dir)?.sync_all()?;
+
+    Ok(())
+}
+
+/// Move the journal out of the way without replaying it
+pub fn set_aside_journal(dir: &Path, reason: &str) -> std::io::Result<Option<PathBuf>> {
+    let path = journal_path(dir);
+    if !path.exists() {
+        return Ok(None);
+    }
+    let target = dir.join(format!(
+        "{}.{}.{}",
+        JOURNAL_FILE,
+        Local::now().format("%Y%m%d-%H%M%S"),
+        reason
+    ));
+    fs::rename(&path, &target)?;
+    Ok(Some(target))
+}
@@ -0,0 +1,466 @@
+use chrono::Local;
+use serde_json::Value;
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use super::journal::{
+    BadJournal, JournalEntry, LibraryEvent, append_journal, archive_journal, drop_torn_tail, journal_path,
+    read_journal, set_aside_journal,
+};
+use super::{
+    BookId, BookProgress, CorruptLibrary, Library, LibraryBackend, LibraryStatus, Note, Parsed,
+    ReadingSession, init_storage, parse_document,
+};
+
+const LIBRARY_FILE: &str = "library.json";
+
+pub fn library_path(dir: &Path) -> PathBuf {
+    dir.join(LIBRARY_FILE)
+}
+
+/// Parse a library file, migrating older schema versions in memory
+fn read_library(path: &Path) -> std::io::Result<Parsed> {
+    let data = fs::read_to_string(path)?;
+    match serde_json::from_str::<Value>(&data) {
+        Ok(doc) => Ok(parse_document(doc)),
+        Err(e) => Ok(Parsed::Corrupt(e)),
+    }
+}
+
+/// Path of the nth most recent backup (1 is the newest)
+fn backup_path(dir: &Path, n: usize) -> PathBuf {
+    dir.join(format!("{}.bak.{}", LIBRARY_FILE, n))
+}
+
+/// Most recent backup that still parses, if any
+fn find_good_backup(dir: &Path) -> Option<PathBuf> {
+    (1..)
+        .map(|n| backup_path(dir, n))
+        .take_while(|path| path.exists())
+        .find(|path| matches!(read_library(path), Ok(Parsed::Ok { .. })))
+}
+
+/// Shift existing backups down by one and copy the current library into slot 1
+fn rotate_backups(dir: &Path, count: usize) -> std::io::Result<()> {
+    let current = library_path(dir);
+    if count == 0 || !current.exists() {
+        return Ok(());
+    }
+
+    for n in (1..count).rev() {
+        let from = backup_path(dir, n);
+        if from.exists() {
+            fs::rename(&from, backup_path(dir, n + 1))?;
+        }
+    }
+    fs::copy(current, backup_path(dir, 1))?;
+    Ok(())
+}
+
+/// Write `data` to `path` so that readers only ever see the old or the new contents
+///
+/// The data goes to a temporary file which is flushed to disk before being
+/// renamed over the target, then the directory entry itself is synced.
+pub(crate) fn write_atomic(path: &Path, data: &[u8]) -> std::io::Result<()> {
+    let mut temp = path.as_os_str().to_owned();
+    temp.push(".tmp");
+    let temp = PathBuf::from(temp);
+
+    let mut file = File::create(&temp)?;
+    file.write_all(data)?;
+    file.sync_all()?;
+    drop(file);
+
+    fs::rename(&temp, path)?;
+
+    #[cfg(unix)]
+    if let Some(parent) = path.parent() {
+        File::open(parent)?.sync_all()?;
+    }
+
+    Ok(())
+}
+
+/// Replace the library file with the given backup
+///
+/// The journal belongs to the snapshot being replaced, so it is set aside.
+pub fn restore_backup(dir: &Path, backup: &Path) -> std::io::Result<()> {
+    let library = match read_library(backup)? {
+        Parsed::Ok { library, .. } => library,
+        Parsed::Corrupt(e) => return Err(io::Error::new(io::ErrorKind::InvalidData, e)),
+        Parsed::TooNew(version) => {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("backup uses newer schema version {}", version),
+            ));
+        }
+    };
+    set_aside_journal(dir, "discarded")?;
+    write_atomic(&library_path(dir), serde_json::to_string_pretty(&library)?.as_bytes())
+}
+
+/// Cut the journal off at its first unreadable line, keeping the entries before it
+///
+/// The snapshot is left alone; the full journal was already quarantined.
+pub fn truncate_journal(dir: &Path, bad_line: usize) -> std::io::Result<()> {
+    let path = journal_path(dir);
+    let kept: String = fs::read_to_string(&path)?
+        .lines()
+        .take(bad_line.saturating_sub(1))
+        .map(|line| format!("{}\n", line))
+        .collect();
+    write_atomic(&path, kept.as_bytes())
+}
+
+/// Set the (already quarantined) journal aside and keep the snapshot as it is
+pub fn discard_journal(dir: &Path) -> std::io::Result<()> {
+    set_aside_journal(dir, "discarded")?;
+    Ok(())
+}
+
+/// The library with its journal replayed, without writing anything
+///
+/// Older schema versions are upgraded in memory only. `None` if either file
+/// cannot be read.
+pub fn peek_library(dir: &Path) -> Option<Library> {
+    let path = library_path(dir);
+    let mut library = if path.exists() {
+        match read_library(&path).ok()? {
+            Parsed::Ok { library, .. } => library,
+            _ => return None,
+        }
+    } else {
+        Library::new()
+    };
+    replay(&mut library, &read_journal(dir).ok()?);
+    Some(library)
+}
+
+/// Apply the journal entries the snapshot does not contain yet
+///
+/// Entries at or below the snapshot's sequence number were already compacted
+/// into it before a crash kept the journal from being archived.
+fn replay(library: &mut Library, entries: &[JournalEntry]) {
+    let compacted = library.journal_seq;
+    for entry in entries.iter().filter(|entry| entry.seq > compacted) {
+        entry.event.apply(library);
+        library.journal_seq = entry.seq;
+    }
+}
+
+/// Discard the (already quarantined) library file and journal and start empty
+pub fn reset_library(dir: &Path) -> std::io::Result<()> {
+    set_aside_journal(dir, "discarded")?;
+    let json = serde_json::to_string_pretty(&Library::new())?;
+    write_atomic(&library_path(dir), json.as_bytes())
+}
+
+fn quarantine(path: &Path) -> std::io::Result<PathBuf> {
+    let mut quarantined = path.as_os_str().to_owned();
+    quarantined.push(format!(".{}.corrupt", Local::now().format("%Y%m%d-%H%M%S")));
+    let quarantined = PathBuf::from(quarantined);
+    fs::copy(path, &quarantined)?;
+    Ok(quarantined)
+}
+
+/// Journal entries written before the snapshot is rewritten
+const COMPACT_AFTER: usize = 500;
+
+/// The library as a JSON snapshot plus an append-only journal of changes
+///
+/// `library.json` holds the state as of `journal_seq`; every later change is an
+/// event line in `journal.jsonl`, replayed on load. Once the journal grows past
+/// `COMPACT_AFTER` entries (and on close) the snapshot is rewritten, rotating
+/// the previous version into the numbered backups, and the journal is moved
+/// into `history/`.
+pub struct JsonBackend {
+    dir: PathBuf,
+    backup_count: usize,
+    library: Library,
+    /// Sequence number of the first entry in the current journal file
+    journal_start: Option<u64>,
+    journal_len: usize,
+    pending: Vec<JournalEntry>,
+}
+
+impl JsonBackend {
+    pub fn new(dir: PathBuf, backup_count: usize) -> Self {
+        Self {
+            dir,
+            backup_count,
+            library: Library::new(),
+            journal_start: None,
+            journal_len: 0,
+            pending: Vec::new(),
+        }
+    }
+
+    fn record(&mut self, event: LibraryEvent) {
+        event.apply(&mut self.library);
+        self.library.journal_seq += 1;
+        self.pending.push(JournalEntry::new(self.library.journal_seq, event));
+    }
+
+    /// Rewrite the snapshot with everything in the journal and archive the journal
+    fn compact(&mut self) -> std::io::Result<()> {
+        init_storage(&self.dir)?;
+        let json = serde_json::to_string_pretty(&self.library)?;
+        rotate_backups(&self.dir, self.backup_count)?;
+        write_atomic(&library_path(&self.dir), json.as_bytes())?;
+
+        if let Some(first) = self.journal_start.take() {
+            archive_journal(&self.dir, first, self.library.journal_seq)?;
+        }
+        self.journal_len = 0;
+        Ok(())
+    }
+
+    fn corrupt(&self, path: &Path, error: String, line: usize, column: usize) -> std::io::Result<LibraryStatus> {
+        Ok(LibraryStatus::Corrupt(CorruptLibrary {
+            error,
+            line,
+            column,
+            quarantined: quarantine(path)?,
+            backup: find_good_backup(&self.dir),
+            in_journal: false,
+        }))
+    }
+
+    /// The snapshot read fine, so recovery only has to deal with the journal
+    fn corrupt_journal(&self, bad: BadJournal) -> std::io::Result<LibraryStatus> {
+        Ok(LibraryStatus::Corrupt(CorruptLibrary {
+            error: bad.error,
+            line: bad.line,
+            column: 0,
+            quarantined: quarantine(&journal_path(&self.dir))?,
+            backup: None,
+            in_journal: true,
+        }))
+    }
+}
+
+impl LibraryBackend for JsonBackend {
+    /// Load the snapshot and replay the journal, quarantining whichever fails to parse
+    fn load(&mut self) -> std::io::Result<LibraryStatus> {
+        init_storage(&self.dir)?;
+        self.pending.clear();
+
+        let path = library_path(&self.dir);
+        let (mut library, migrated_from) = if path.exists() {
+            match read_library(&path)? {
+                Parsed::Ok { library, migrated_from } => (library, migrated_from),
+                Parsed::TooNew(version) => return Ok(LibraryStatus::TooNew(version)),
+                Parsed::Corrupt(e) => return self.corrupt(&path, e.to_string(), e.line(), e.column()),
+            }
+        } else {
+            (Library::new(), None)
+        };
+
+        drop_torn_tail(&self.dir)?;
+        let entries = match read_journal(&self.dir) {
+            Ok(entries) => entries,
+            Err(bad) => return self.corrupt_journal(bad),
+        };
+        self.journal_start = entries.first().map(|entry| entry.seq);
+        self.journal_len = entries.len();
+        replay(&mut library, &entries);
+        self.library = library;
+
+        if let Some(version) = migrated_from {
+            // Keep the file as it was before the upgrade, then persist the upgrade
+            let backup = self.dir.join(format!("{}.v{}.bak", LIBRARY_FILE, version));
+            if !backup.exists() {
+                fs::copy(&path, &backup)?;
+            }
+            self.compact()?;
+        }
+
+        Ok(LibraryStatus::Loaded(self.library.clone()))
+    }
+
+    fn add_book(&mut self, book: &BookProgress) -> std::io::Result<()> {
+        self.record(LibraryEvent::BookAdded { book: book.clone() });
+        Ok(())
+    }
+
+    fn append_session(&mut self, book_id: BookId, session: &ReadingSession) -> std::io::Result<()> {
+        self.record(LibraryEvent::SessionRecorded {
+            book_id,
+            session: session.clone(),
+        });
+        Ok(())
+    }
+
+    fn set_current_page(&mut self, book_id: BookId, current_page: usize) -> std::io::Result<()> {
+        let previous_page = match self.library.book(book_id) {
+            Some(book) if book.current_page != current_page => book.current_page,
+            _ => return Ok(()),
+        };
+        self.record(LibraryEvent::PageSet {
+            book_id,
+            previous_page,
+            current_page,
+        });
+        Ok(())
+    }
+
+    fn add_note(&mut self, book_id: BookId, note: &Note) -> std::io::Result<()> {
+        self.record(LibraryEvent::NoteAdded {
+            book_id,
+            note: note.clone(),
+        });
+        Ok(())
+    }
+
+    fn update_book(&mut self, book: &BookProgress) -> std::io::Result<()> {
+        self.record(LibraryEvent::BookEdited { book: book.clone() });
+        Ok(())
+    }
+
+    fn delete_book(&mut self, book_id: BookId) -> std::io::Result<()> {
+        if let Some(book) = self.library.book(book_id) {
+            let book_title = book.book_title.clone();
+            self.record(LibraryEvent::BookDeleted { book_id, book_title });
+        }
+        Ok(())
+    }
+
+    /// Append pending events to the journal, compacting once it has grown large
+    fn flush(&mut self) -> std::io::Result<()> {
+        if self.pending.is_empty() {
+            return Ok(());
+        }
+
+        init_storage(&self.dir)?;
+        append_journal(&self.dir, &self.pending)?;
+        self.journal_start.get_or_insert(self.pending[0].seq);
+        self.journal_len += self.pending.len();
+        self.pending.clear();
+
+        if self.journal_len >= COMPACT_AFTER {
+            self.compact()?;
+        }
+        Ok(())
+    }
+
+    fn close(&mut self) -> std::io::Result<()> {
+        self.flush()?;
+        if self.journal_len > 0 {
+            self.compact()?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::ReadingStatus;
+
+    /// A crash leaves a journal behind, and the next build may have a newer schema
+    #[test]
+    fn replays_journal_from_older_schema() {
+        let dir = std::env::temp_dir().join(format!("book_garden-test-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        let book_id = "11cddd93-edf3-43b0-a41e-5412c379a931";
+        fs::write(
+            library_path(&dir),
+            serde_json::json!({
+                "schema_version": 2,
+                "journal_seq": 0,
+                "books": [{
+                    "id": book_id,
+                    "book_title": "Dune",
+                    "total_pages": 300,
+                    "current_page": 0,
+                    "sessions": [],
+                }],
+            })
+            .to_string(),
+        )
+        .unwrap();
+        let session = serde_json::json!({
+            "seq": 1,
+            "at": "2024-05-01T20:00:00+02:00",
+            "schema_version": 2,
+            "event": "session_recorded",
+            "book_id": book_id,
+            "session": {
+                "book_title": "Dune",
+                "start_page": 0,
+                "end_page": 40,
+                "duration_secs": 1800,
+                "timestamp": "2024-05-01T20:00:00+02:00",
+            },
+        });
+        let added = serde_json::json!({
+            "seq": 2,
+            "at": "2024-05-01T20:05:00+02:00",
+            "schema_version": 2,
+            "event": "book_added",
+            "book": {
+                "id": "5b1f0a52-7f0e-4c4e-9b6b-2d2f4f0c8a11",
+                "book_title": "Emma",
+                "total_pages": 400,
+                "current_page": 0,
+                "sessions": [],
+            },
+        });
+        fs::write(journal_path(&dir), format!("{}\n{}\n", session, added)).unwrap();
+
+        let status = JsonBackend::new(dir.clone(), 0).load().unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+
+        let LibraryStatus::Loaded(library) = status else {
+            panic!("journal was not replayed");
+        };
+        assert_eq!(library.journal_seq, 2);
+        assert_eq!(library.books.len(), 2);
+        let book = &library.books[0];
+        assert_eq!(book.current_page, 40);
+        assert_eq!(book.status, ReadingStatus::Reading);
+        let session = &book.sessions[0];
+        assert_eq!((session.timestamp - session.started_at).num_seconds(), 1800);
+        assert_eq!(session.intervals.len(), 1);
+        assert_eq!(library.books[1].book_title, "Emma");
+    }
+
+    /// An append cut short by a crash must not swallow the next run's first entry
+    #[test]
+    fn appends_after_torn_journal_line() {
+        let dir = std::env::temp_dir().join(format!("book_garden-test-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        let book_id: BookId = "11cddd93-edf3-43b0-a41e-5412c379a931".parse().unwrap();
+        fs::write(
+            library_path(&dir),
+            serde_json::json!({
+                "schema_version": 2,
+                "journal_seq": 0,
+                "books": [{ "id": book_id, "book_title": "Dune", "total_pages": 300, "current_page": 0, "sessions": [] }],
+            })
+            .to_string(),
+        )
+        .unwrap();
+
+        let mut backend = JsonBackend::new(dir.clone(), 0);
+        backend.load().unwrap();
+        backend.set_current_page(book_id, 40).unwrap();
+        backend.flush().unwrap();
+        let mut journal = fs::OpenOptions::new().append(true).open(journal_path(&dir)).unwrap();
+        journal.write_all(br#"{"seq":2,"at":"2024-05-01T2"#).unwrap();
+
+        let mut backend = JsonBackend::new(dir.clone(), 0);
+        backend.load().unwrap();
+        backend.set_current_page(book_id, 50).unwrap();
+        backend.flush().unwrap();
+        let status = JsonBackend::new(dir.clone(), 0).load().unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+
+        let LibraryStatus::Loaded(library) = status else {
+            panic!("journal with a torn line was not readable after the next append");
+        };
+        assert_eq!(library.journal_seq, 2);
+        assert_eq!(library.books[0].current_page, 50);
+    }
+}
@@ -0,0 +1,209 @@
+use rusqlite::{Connection, OpenFlags, OptionalExtension, params};
+use serde_json::Value;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use super::{
+    BookId, BookProgress, Library, LibraryBackend, LibraryStatus, Note, Parsed, ReadingSession,
+    SCHEMA_VERSION, init_storage, parse_document,
+};
+
+const DATABASE_FILE: &str = "library.sqlite3";
+
+pub fn database_path(dir: &Path) -> PathBuf {
+    dir.join(DATABASE_FILE)
+}
+
+fn sql_error(e: rusqlite::Error) -> io::Error {
+    io::Error::other(e)
+}
+
+/// Reassemble the rows into a library document at the database's schema version
+fn read_document(conn: &Connection) -> std::io::Result<Value> {
+    let version: Option<String> = conn
+        .query_row("SELECT value FROM meta WHERE key = 'schema_version'", [], |row| row.get(0))
+        .optional()
+        .map_err(sql_error)?;
+    let version: u32 = match version {
+        Some(v) => v
+            .parse()
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?,
+        None => SCHEMA_VERSION,
+    };
+
+    let mut stmt = conn
+        .prepare("SELECT data FROM books ORDER BY position")
+        .map_err(sql_error)?;
+    let books = stmt
+        .query_map([], |row| row.get::<_, String>(0))
+        .map_err(sql_error)?
+        .map(|data| {
+            let data = data.map_err(sql_error)?;
+            Ok(serde_json::from_str::<Value>(&data)?)
+        })
+        .collect::<std::io::Result<Vec<Value>>>()?;
+
+    // The journal sequence only has meaning for the JSON backend
+    Ok(serde_json::json!({ "schema_version": version, "journal_seq": 0, "books": books }))
+}
+
+/// The library as stored, opening the database read-only
+///
+/// Older schema versions are upgraded in memory only. `None` if the database
+/// cannot be read.
+pub fn peek_library(dir: &Path) -> Option<Library> {
+    let path = database_path(dir);
+    if !path.exists() {
+        return Some(Library::new());
+    }
+    let conn = Connection::open_with_flags(&path, OpenFlags::SQLITE_OPEN_READ_ONLY).ok()?;
+    match parse_document(read_document(&conn).ok()?) {
+        Parsed::Ok { library, .. } => Some(library),
+        _ => None,
+    }
+}
+
+/// The library in an embedded SQLite database
+///
+/// Each book is one row holding its JSON representation, so a change only
+/// rewrites that book and every statement commits on its own.
+pub struct SqliteBackend {
+    dir: PathBuf,
+    conn: Option<Connection>,
+}
+
+impl SqliteBackend {
+    pub fn new(dir: PathBuf) -> Self {
+        Self { dir, conn: None }
+    }
+
+    fn conn(&mut self) -> std::io::Result<&mut Connection> {
+        if self.conn.is_none() {
+            init_storage(&self.dir)?;
+            let conn = Connection::open(database_path(&self.dir)).map_err(sql_error)?;
+            conn.execute_batch(
+                "PRAGMA journal_mode = WAL;
+                 PRAGMA synchronous = FULL;
+                 CREATE TABLE IF NOT EXISTS meta (
+                     key TEXT PRIMARY KEY,
+                     value TEXT NOT NULL
+                 );
+                 CREATE TABLE IF NOT EXISTS books (
+                     id TEXT PRIMARY KEY,
+                     position INTEGER NOT NULL,
+                     data TEXT NOT NULL
+                 );",
+            )
+            .map_err(sql_error)?;
+            self.conn = Some(conn);
+        }
+        Ok(self.conn.as_mut().expect("connection was just opened"))
+    }
+
+    /// Read, change and write back a single book inside one transaction
+    fn modify(&mut self, book_id: BookId, f: impl FnOnce(&mut BookProgress)) -> std::io::Result<()> {
+        let tx = self.conn()?.transaction().map_err(sql_error)?;
+        let data: Option<String> = tx
+            .query_row("SELECT data FROM books WHERE id = ?1", [book_id.to_string()], |row| row.get(0))
+            .optional()
+            .map_err(sql_error)?;
+
+        if let Some(data) = data {
+            let mut book: BookProgress = serde_json::from_str(&data)?;
+            f(&mut book);
+            tx.execute(
+                "UPDATE books SET data = ?2 WHERE id = ?1",
+                params![book_id.to_string(), serde_json::to_string(&book)?],
+            )
+            .map_err(sql_error)?;
+        }
+
+        tx.commit().map_err(sql_error)
+    }
+
+    fn write_all_books(&mut self, books: &[BookProgress]) -> std::io::Result<()> {
+        let tx = self.conn()?.transaction().map_err(sql_error)?;
+        tx.execute("DELETE FROM books", []).map_err(sql_error)?;
+        for (position, book) in books.iter().enumerate() {
+            tx.execute(
+                "INSERT INTO books (id, position, data) VALUES (?1, ?2, ?3)",
+                params![book.id.to_string(), position as i64, serde_json::to_string(book)?],
+            )
+            .map_err(sql_error)?;
+        }
+        tx.execute(
+            "INSERT OR REPLACE INTO meta (key, value) VALUES ('schema_version', ?1)",
+            [SCHEMA_VERSION.to_string()],
+        )
+        .map_err(sql_error)?;
+        tx.commit().map_err(sql_error)
+    }
+}
+
+impl LibraryBackend for SqliteBackend {
+    /// Reassemble the rows into a library document and run it through the usual migrations
+    fn load(&mut self) -> std::io::Result<LibraryStatus> {
+        let doc = read_document(self.conn()?)?;
+        match parse_document(doc) {
+            Parsed::Ok { library, migrated_from } => {
+                if let Some(version) = migrated_from {
+                    // Keep the database as it was before the upgrade, then persist the upgrade
+                    let backup = self.dir.join(format!("{}.v{}.bak", DATABASE_FILE, version));
+                    if !backup.exists() {
+                        self.conn()?
+                            .execute("VACUUM INTO ?1", [backup.to_string_lossy()])
+                            .map_err(sql_error)?;
+                    }
+                    self.write_all_books(&library.books)?;
+                }
+                Ok(LibraryStatus::Loaded(library))
+            }
+            Parsed::TooNew(version) => Ok(LibraryStatus::TooNew(version)),
+            Parsed::Corrupt(e) => Err(io::Error::new(io::ErrorKind::InvalidData, e)),
+        }
+    }
+
+    fn add_book(&mut self, book: &BookProgress) -> std::io::Result<()> {
+        let conn = self.conn()?;
+        conn.execute(
+            "INSERT INTO books (id, position, data)
+             VALUES (?1, (SELECT COALESCE(MAX(position) + 1, 0) FROM books), ?2)",
+            params![book.id.to_string(), serde_json::to_string(book)?],
+        )
+        .map_err(sql_error)?;
+        conn.execute(
+            "INSERT OR IGNORE INTO meta (key, value) VALUES ('schema_version', ?1)",
+            [SCHEMA_VERSION.to_string()],
+        )
+        .map_err(sql_error)?;
+        Ok(())
+    }
+
+    fn append_session(&mut self, book_id: BookId, session: &ReadingSession) -> std::io::Result<()> {
+        self.modify(book_id, |book| book.record_session(session.clone()))
+    }
+
+    fn set_current_page(&mut self, book_id: BookId, current_page: usize) -> std::io::Result<()> {
+        self.modify(book_id, |book| book.current_page = current_page)
+    }
+
+    fn add_note(&mut self, book_id: BookId, note: &Note) -> std::io::Result<()> {
+        self.modify(book_id, |book| book.notes.push(note.clone()))
+    }
+
+    fn update_book(&mut self, book: &BookProgress) -> std::io::Result<()> {
+        self.modify(book.id, |existing| *existing = book.clone())
+    }
+
+    fn delete_book(&mut self, book_id: BookId) -> std::io::Result<()> {
+        self.conn()?
+            .execute("DELETE FROM books WHERE id = ?1", [book_id.to_string()])
+            .map_err(sql_error)?;
+        Ok(())
+    }
+
+    /// Every change is committed as it happens
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
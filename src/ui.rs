@@ -2,12 +2,14 @@ use ratatui::{
     layout::{Alignment, Constraint, Direction, Layout},
     style::{Color, Modifier, Style},
     text::{Line, Span},
-    widgets::{Block, Borders, Gauge, Paragraph},
+    widgets::{Block, Borders, Clear, Gauge, Paragraph, Wrap},
     Frame,
 };
 
-use crate::app::{AppState, TimerState};
+use crate::app::{AppState, FocusPhase, NextUp, RatingStage, TimerState};
+use crate::book_select::{centered_rect, format_stars};
 use crate::plant::create_plant_canvas;
+use crate::storage::{format_hms, BookMetadata, NoteKind, ProgressUnit, ReadingStatus};
 
 pub fn draw_ui(f: &mut Frame, app: &AppState) {
     let main_chunks = Layout::default()
@@ -27,11 +29,43 @@ pub fn draw_ui(f: &mut Frame, app: &AppState) {
         ])
         .split(main_chunks[0]);
 
+    // Title line plus up to three metadata lines
+    let mut title_spans = vec![
+        Span::styled(
+            &app.book_title,
+            Style::default()
+                .fg(Color::Cyan)
+                .add_modifier(Modifier::BOLD),
+        ),
+    ];
+    if app.read_number > 1 {
+        title_spans.push(Span::styled(
+            format!("  Read #{}", app.read_number),
+            Style::default().fg(Color::Magenta),
+        ));
+    }
+    let mut title_lines = vec![Line::from(title_spans)];
+    if let Some(series) = &app.metadata.series {
+        title_lines.push(Line::from(Span::styled(
+            format!("{}, {}", series.name, series.position_label()),
+            Style::default().fg(Color::Yellow),
+        )));
+    }
+    if !app.metadata.authors.is_empty() {
+        title_lines.push(Line::from(Span::styled(
+            format!("by {}", app.metadata.authors.join(", ")),
+            Style::default().fg(Color::White),
+        )));
+    }
+    if let Some(details) = metadata_details(&app.metadata) {
+        title_lines.push(Line::from(Span::styled(details, Style::default().fg(Color::Gray))));
+    }
+
     let chunks = Layout::default()
         .direction(Direction::Vertical)
         .margin(1)
         .constraints([
-            Constraint::Length(3), // Title
+            Constraint::Length(title_lines.len() as u16 + 2), // Title
             Constraint::Length(3), // Timer
             Constraint::Length(3), // Page counter
             Constraint::Length(3), // Progress bar
@@ -40,16 +74,13 @@ pub fn draw_ui(f: &mut Frame, app: &AppState) {
         .split(content_chunks[0]);
 
     // Title
-    let title = Paragraph::new(Line::from(vec![
-        Span::styled(
-            &app.book_title,
-            Style::default()
-                .fg(Color::Cyan)
-                .add_modifier(Modifier::BOLD),
-        ),
-    ]))
+    let title = Paragraph::new(title_lines)
     .alignment(Alignment::Center)
-    .block(Block::default().borders(Borders::ALL).title("Book Tracker"));
+    .block(Block::default().borders(Borders::ALL).title(if app.read_only {
+        "Book Tracker (read-only, not saved)"
+    } else {
+        "Book Tracker"
+    }));
 
     f.render_widget(title, chunks[0]);
 
@@ -60,18 +91,32 @@ pub fn draw_ui(f: &mut Frame, app: &AppState) {
     let seconds = elapsed.as_secs() % 60;
 
     let timer_text = format!("{:02}:{:02}:{:02}", hours, minutes, seconds);
+    let on_break = matches!(app.focus, Some(FocusPhase::Break { .. }));
     let timer_color = match app.timer_state {
+        TimerState::Paused if on_break => Color::Blue,
         TimerState::Running => Color::Green,
         TimerState::Paused => Color::Yellow,
         TimerState::Stopped => Color::Red,
     };
 
     let timer_status = match app.timer_state {
+        TimerState::Paused if on_break => "BREAK",
         TimerState::Running => "READING",
         TimerState::Paused => "PAUSED",
         TimerState::Stopped => "STOPPED",
     };
 
+    let timer_title = match app.focus_remaining() {
+        Some(left) => format!(
+            "Time - {} {} left, {} block{} done",
+            if on_break { "Break" } else { "Focus" },
+            format_hms(left.as_secs() as usize),
+            app.focus_blocks,
+            if app.focus_blocks == 1 { "" } else { "s" }
+        ),
+        None => "Time".to_string(),
+    };
+
     let timer = Paragraph::new(Line::from(vec![
         Span::styled(
             format!("{} ", timer_status),
@@ -87,15 +132,37 @@ pub fn draw_ui(f: &mut Frame, app: &AppState) {
         ),
     ]))
     .alignment(Alignment::Center)
-    .block(Block::default().borders(Borders::ALL).title("Time"));
+    .block(Block::default().borders(Borders::ALL).title(timer_title).title_bottom(
+        app.checkpoint_error.as_ref().map_or_else(Line::default, |e| {
+            Line::styled(format!("Checkpoint failed: {e}"), Style::default().fg(Color::Red))
+        }),
+    ));
 
     f.render_widget(timer, chunks[1]);
 
     // Page counter
-    let pages_text = format!("Page {} of {}", app.current_page, app.total_pages);
+    let pages_text = match app.unit {
+        ProgressUnit::Pages => format!("Page {} of {}", app.current_page, app.total_pages),
+        ProgressUnit::Percent => format!("{}% read", app.current_page),
+        ProgressUnit::Locations => format!("Location {} of {}", app.current_page, app.total_pages),
+        ProgressUnit::Audio => format!(
+            "{} of {}",
+            format_hms(app.current_page),
+            format_hms(app.total_pages)
+        ),
+    };
+    let progress_title = if app.range.is_partial(app.total_pages) {
+        format!(
+            "Progress (counting {}–{})",
+            app.unit.format_position(app.range.first),
+            app.unit.format_position(app.range.last)
+        )
+    } else {
+        "Progress".to_string()
+    };
     let pages = Paragraph::new(pages_text)
         .alignment(Alignment::Center)
-        .block(Block::default().borders(Borders::ALL).title("Progress"))
+        .block(Block::default().borders(Borders::ALL).title(progress_title))
         .style(Style::default().fg(Color::White));
 
     f.render_widget(pages, chunks[2]);
@@ -117,41 +184,114 @@ pub fn draw_ui(f: &mut Frame, app: &AppState) {
 
     // Session stats
     let pages_this_session = app.pages_read_this_session();
-    let total_hours = app.total_time_secs / 3600;
-    let total_minutes = (app.total_time_secs % 3600) / 60;
+    // The labels already say "pages" for paper books
+    let amount = |value: usize| match app.unit {
+        ProgressUnit::Pages => value.to_string(),
+        unit => unit.format_amount(value),
+    };
+    let this_session = if pages_this_session < 0 {
+        format!("-{}", amount(pages_this_session.unsigned_abs() as usize))
+    } else {
+        amount(pages_this_session as usize)
+    };
+    let remaining = amount(app.range.remaining(app.current_page));
+    let (this_session_label, remaining_label) = match app.unit {
+        ProgressUnit::Pages => ("Pages this session: ", "Pages remaining: "),
+        ProgressUnit::Audio => ("Listened this session: ", "Time remaining: "),
+        _ => ("Read this session: ", "Remaining: "),
+    };
+    let hours_minutes = |secs: u64| format!("{}h {}m", secs / 3600, (secs % 3600) / 60);
+
+    let date = |at: Option<chrono::DateTime<chrono::Local>>| {
+        at.map(|at| at.format("%Y-%m-%d").to_string()).unwrap_or_else(|| "?".to_string())
+    };
+    let status_text = match &app.status {
+        ReadingStatus::WantToRead => "Want to read".to_string(),
+        ReadingStatus::Reading => format!("Reading since {}", date(app.started_at)),
+        ReadingStatus::Finished => format!("Finished {}", date(app.finished_at)),
+        ReadingStatus::Abandoned { reason } if reason.is_empty() => {
+            format!("Abandoned {}", date(app.finished_at))
+        }
+        ReadingStatus::Abandoned { reason } => {
+            format!("Abandoned {} ({})", date(app.finished_at), reason)
+        }
+    };
+    let status_color = match app.status {
+        ReadingStatus::Finished => Color::Green,
+        ReadingStatus::Abandoned { .. } => Color::Red,
+        _ => Color::Cyan,
+    };
 
-    let stats_text = vec![
+    let mut stats_text = vec![
+        Line::from(vec![
+            Span::styled("Status: ", Style::default().fg(Color::Gray)),
+            Span::styled(status_text, Style::default().fg(status_color)),
+        ]),
         Line::from(vec![
             Span::styled("Total time: ", Style::default().fg(Color::Gray)),
             Span::styled(
-                format!("{}h {}m", total_hours, total_minutes),
+                hours_minutes(app.run_stats.total_time_secs),
                 Style::default()
                     .fg(Color::Cyan)
                     .add_modifier(Modifier::BOLD),
             ),
             Span::styled("  Sessions: ", Style::default().fg(Color::Gray)),
             Span::styled(
-                format!("{}", app.total_sessions),
+                format!("{}", app.run_stats.total_sessions),
+                Style::default().fg(Color::Cyan),
+            ),
+            Span::styled("  Notes: ", Style::default().fg(Color::Gray)),
+            Span::styled(
+                format!("{}", app.note_count),
                 Style::default().fg(Color::Cyan),
             ),
         ]),
         Line::from(vec![
-            Span::styled("Pages this session: ", Style::default().fg(Color::Gray)),
+            Span::styled(this_session_label, Style::default().fg(Color::Gray)),
             Span::styled(
-                format!("{}", pages_this_session),
+                this_session,
                 Style::default()
                     .fg(Color::Green)
                     .add_modifier(Modifier::BOLD),
             ),
         ]),
         Line::from(vec![
-            Span::styled("Pages remaining: ", Style::default().fg(Color::Gray)),
+            Span::styled(remaining_label, Style::default().fg(Color::Gray)),
             Span::styled(
-                format!("{}", app.total_pages - app.current_page),
+                remaining,
                 Style::default().fg(Color::Yellow),
             ),
         ]),
     ];
+    if let (Some(chapter), Some(left)) = (app.current_chapter(), app.left_in_chapter()) {
+        stats_text.push(Line::from(vec![
+            Span::styled("Chapter: ", Style::default().fg(Color::Gray)),
+            Span::styled(chapter.title.clone(), Style::default().fg(Color::Cyan)),
+            Span::styled(
+                format!("  ({} left)", app.unit.format_amount(left)),
+                Style::default().fg(Color::Gray),
+            ),
+        ]));
+    }
+    if let Some(rating) = app.rating {
+        stats_text.push(Line::from(vec![
+            Span::styled("Rating: ", Style::default().fg(Color::Gray)),
+            Span::styled(format_stars(rating), Style::default().fg(Color::Yellow)),
+        ]));
+    }
+    if app.read_number > 1 {
+        stats_text.push(Line::from(vec![
+            Span::styled("All reads: ", Style::default().fg(Color::Gray)),
+            Span::styled(
+                format!(
+                    "{} in {} sessions",
+                    hours_minutes(app.lifetime_stats.total_time_secs),
+                    app.lifetime_stats.total_sessions
+                ),
+                Style::default().fg(Color::Magenta),
+            ),
+        ]));
+    }
 
     let stats = Paragraph::new(stats_text)
         .block(Block::default().borders(Borders::ALL).title("Statistics"))
@@ -166,24 +306,235 @@ pub fn draw_ui(f: &mut Frame, app: &AppState) {
     f.render_widget(plant, content_chunks[1]);
 
     // Legend bar at the bottom
-    let legend = Line::from(vec![
+    let mut legend_spans = vec![
         Span::raw(" "),
         Span::styled("Space", Style::default().fg(Color::Black).bg(Color::Cyan).add_modifier(Modifier::BOLD)),
         Span::raw(" Start/Pause  "),
         Span::styled("S", Style::default().fg(Color::Black).bg(Color::Cyan).add_modifier(Modifier::BOLD)),
         Span::raw(" Stop  "),
         Span::styled("↑/↓", Style::default().fg(Color::Black).bg(Color::Cyan).add_modifier(Modifier::BOLD)),
-        Span::raw(" ±10 pages  "),
+        Span::raw(format!(" ±{}  ", app.unit.format_amount(app.unit.large_step() as usize))),
         Span::styled("→/←", Style::default().fg(Color::Black).bg(Color::Cyan).add_modifier(Modifier::BOLD)),
-        Span::raw(" ±1 page  "),
+        Span::raw(match app.unit {
+            ProgressUnit::Pages => " ±1 page  ".to_string(),
+            unit => format!(" ±{}  ", unit.format_amount(unit.small_step() as usize)),
+        }),
+    ];
+    if !app.chapters.is_empty() {
+        legend_spans.push(Span::styled("N", Style::default().fg(Color::Black).bg(Color::Cyan).add_modifier(Modifier::BOLD)));
+        legend_spans.push(Span::raw(" Next chapter  "));
+    }
+    legend_spans.extend([
+        Span::styled("F", Style::default().fg(Color::Black).bg(Color::Cyan).add_modifier(Modifier::BOLD)),
+        Span::raw(if app.focus.is_some() { " Focus off  " } else { " Focus  " }),
+        Span::styled("W", Style::default().fg(Color::Black).bg(Color::Cyan).add_modifier(Modifier::BOLD)),
+        Span::raw(" Note  "),
+        Span::styled("V", Style::default().fg(Color::Black).bg(Color::Cyan).add_modifier(Modifier::BOLD)),
+        Span::raw(" View notes  "),
         Span::styled("B", Style::default().fg(Color::Black).bg(Color::Cyan).add_modifier(Modifier::BOLD)),
         Span::raw(" Book Select  "),
         Span::styled("Q", Style::default().fg(Color::Black).bg(Color::Cyan).add_modifier(Modifier::BOLD)),
         Span::raw(" Quit"),
     ]);
+    let legend = Line::from(legend_spans);
 
     let legend_widget = Paragraph::new(legend)
         .style(Style::default().bg(Color::DarkGray));
 
     f.render_widget(legend_widget, main_chunks[1]);
+
+    if app.note_draft.is_some() {
+        draw_note_entry(f, app);
+    }
+    if app.rating_prompt.is_some() {
+        draw_rating_prompt(f, app);
+    } else if app.next_up.is_some() {
+        draw_next_up(f, app);
+    }
+    if app.idle_prompt.is_some() {
+        draw_idle_prompt(f, app);
+    }
+}
+
+fn draw_idle_prompt(f: &mut Frame, app: &AppState) {
+    let area = centered_rect(60, 8, f.area());
+    let idle = app.idle_time().as_secs();
+    let idle = if idle >= 3600 {
+        format!("{}h {}m", idle / 3600, (idle % 3600) / 60)
+    } else {
+        format!("{}m", idle / 60)
+    };
+
+    let text = vec![
+        Line::from(Span::styled(
+            format!("No key pressed for {} while the timer was running.", idle),
+            Style::default().fg(Color::White),
+        )),
+        Line::from(""),
+        Line::from(Span::styled(
+            "Y: Yes, keep the time | T: Trim the idle time | P: Pause as of then",
+            Style::default().fg(Color::Cyan),
+        )),
+    ];
+
+    let dialog = Paragraph::new(text)
+        .wrap(Wrap { trim: false })
+        .alignment(Alignment::Center)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title("Still reading?")
+                .border_style(Style::default().fg(Color::Yellow)),
+        );
+
+    f.render_widget(Clear, area);
+    f.render_widget(dialog, area);
+}
+
+fn draw_next_up(f: &mut Frame, app: &AppState) {
+    let (message, action, series) = match &app.next_up {
+        Some(NextUp::Open { book_title, series, .. }) => (
+            format!("Next up: {} ({})", book_title, series.position_label()),
+            "Enter: Open it",
+            series,
+        ),
+        Some(NextUp::Add { series, .. }) => (
+            format!("Book {} is not in your library yet", series.number + 1),
+            "Enter: Add it",
+            series,
+        ),
+        None => return,
+    };
+    let area = centered_rect(60, 7, f.area());
+
+    let text = vec![
+        Line::from(Span::styled(message, Style::default().fg(Color::White))),
+        Line::from(""),
+        Line::from(Span::styled(
+            format!("{} | Esc: Not now", action),
+            Style::default().fg(Color::Cyan),
+        )),
+    ];
+
+    let dialog = Paragraph::new(text)
+        .wrap(Wrap { trim: false })
+        .alignment(Alignment::Center)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(format!("Continue {}?", series.name))
+                .border_style(Style::default().fg(Color::Green)),
+        );
+
+    f.render_widget(Clear, area);
+    f.render_widget(dialog, area);
+}
+
+fn draw_rating_prompt(f: &mut Frame, app: &AppState) {
+    let Some(prompt) = &app.rating_prompt else {
+        return;
+    };
+    let area = centered_rect(60, 12, f.area());
+
+    let (stars_style, review_style, hint) = match prompt.stage {
+        RatingStage::Stars => (
+            Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD),
+            Style::default().fg(Color::Gray),
+            "←/→: Rate | 1-5: Stars | Enter: Review | Tab: Save | Esc: Not now",
+        ),
+        RatingStage::Review => (
+            Style::default().fg(Color::Yellow),
+            Style::default().fg(Color::White),
+            "Enter: New line | Tab: Save | Esc: Back",
+        ),
+    };
+    let review = match prompt.stage {
+        RatingStage::Stars if prompt.review.is_empty() => "(no review)".to_string(),
+        RatingStage::Stars => prompt.review.clone(),
+        RatingStage::Review => format!("{}_", prompt.review),
+    };
+
+    let mut text = vec![
+        Line::from(Span::styled(
+            format!("{}  {:.1}", format_stars(prompt.rating), prompt.rating),
+            stars_style,
+        )),
+        Line::from(""),
+    ];
+    text.extend(review.lines().map(|line| Line::from(Span::styled(line.to_string(), review_style))));
+    if review.ends_with('\n') {
+        text.push(Line::from(""));
+    }
+    text.push(Line::from(""));
+    text.push(Line::from(Span::styled(hint, Style::default().fg(Color::Cyan))));
+
+    let dialog = Paragraph::new(text)
+        .wrap(Wrap { trim: false })
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(format!("Finished {}: rate it?", app.book_title))
+                .border_style(Style::default().fg(Color::Yellow)),
+        );
+
+    f.render_widget(Clear, area);
+    f.render_widget(dialog, area);
+}
+
+fn draw_note_entry(f: &mut Frame, app: &AppState) {
+    let Some(draft) = &app.note_draft else {
+        return;
+    };
+    let area = centered_rect(60, 8, f.area());
+
+    let kind = match draft.kind {
+        NoteKind::Note => "Note",
+        NoteKind::Quote => "Quote",
+    };
+    let text = vec![
+        Line::from(Span::styled(
+            format!("{}_", draft.text),
+            Style::default().fg(Color::White),
+        )),
+        Line::from(""),
+        Line::from(Span::styled(
+            "Enter: Save | Tab: Note/Quote | Esc: Cancel",
+            Style::default().fg(Color::Cyan),
+        )),
+    ];
+
+    let dialog = Paragraph::new(text)
+        .wrap(Wrap { trim: false })
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(format!("{} at {}", kind, app.unit.format_position(app.current_page)))
+                .border_style(Style::default().fg(Color::Yellow)),
+        );
+
+    f.render_widget(Clear, area);
+    f.render_widget(dialog, area);
+}
+
+/// Publisher, year, language, genres and ISBN joined into one line
+fn metadata_details(metadata: &BookMetadata) -> Option<String> {
+    let mut parts = Vec::new();
+
+    match (&metadata.publisher, metadata.year) {
+        (Some(publisher), Some(year)) => parts.push(format!("{}, {}", publisher, year)),
+        (Some(publisher), None) => parts.push(publisher.clone()),
+        (None, Some(year)) => parts.push(year.to_string()),
+        (None, None) => {}
+    }
+    if let Some(language) = &metadata.language {
+        parts.push(language.clone());
+    }
+    if !metadata.genres.is_empty() {
+        parts.push(metadata.genres.join(", "));
+    }
+    if let Some(isbn) = &metadata.isbn {
+        parts.push(format!("ISBN {}", isbn));
+    }
+
+    (!parts.is_empty()).then(|| parts.join(" · "))
 }